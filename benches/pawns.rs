@@ -0,0 +1,103 @@
+// benches/pawns.rs
+
+//! Pawn structure cache benchmark: `cargo bench --bench pawns`.
+//! Times the evaluation of every position of a small tree with and without a `PawnTable`,
+//! then a short game of searches with fresh pawn tables for every move against tables
+//! kept from one search to the next.
+
+use std::time::{Duration, Instant};
+
+use chessr::board::Position;
+use chessr::eval::{evaluate, evaluate_cached, PawnTable, DEFAULT_PARAMS};
+use chessr::search::{think, SearchLimits, SearchSignals, TranspositionTable};
+
+/// Middlegame position whose tree is evaluated
+const MIDDLEGAME: &str = "r1bq1rk1/pp2bppp/2n1pn2/3p4/2PP4/2N1PN2/PP1B1PPP/R2QKB1R w KQ - 0 8";
+
+/// Moves of the searched game, from the starting position
+const GAME: [&str; 16] = [
+    "d2d4", "g8f6", "c2c4", "e7e6", "g1f3", "d7d5", "b1c3", "f8e7",
+    "c1g5", "e8g8", "e2e3", "h7h6", "g5h4", "b7b6", "c4d5", "f6d5",
+];
+
+/// Depth of every search of the game
+const SEARCH_DEPTH: u32 = 7;
+
+/// Runs of each measurement; the fastest counts
+const RUNS: usize = 3;
+
+fn main() {
+    chessr::board::magic::init();
+    bench_evaluation();
+    bench_search();
+}
+
+/// All positions of the tree below `pos` down to `depth` plies
+fn tree(pos: &mut Position, depth: u32, positions: &mut Vec<Position>) {
+    positions.push(pos.clone());
+    if depth == 0 {
+        return;
+    }
+    for &mv in pos.generate_legal_moves().iter() {
+        pos.make_move(mv);
+        tree(pos, depth - 1, positions);
+        pos.unmake_move();
+    }
+}
+
+/// Fastest of `RUNS` timings of `f`, with its result
+fn fastest<T>(mut f: impl FnMut() -> T) -> (Duration, T) {
+    let mut best = None;
+    for _ in 0..RUNS {
+        let start = Instant::now();
+        let result = f();
+        let elapsed = start.elapsed();
+        if best.as_ref().is_none_or(|&(time, _)| elapsed < time) {
+            best = Some((elapsed, result));
+        }
+    }
+    best.unwrap()
+}
+
+fn bench_evaluation() {
+    let mut positions = Vec::new();
+    tree(&mut Position::from_fen(MIDDLEGAME).unwrap(), 3, &mut positions);
+
+    let (plain, plain_sum) =
+        fastest(|| positions.iter().map(|pos| evaluate(pos) as i64).sum::<i64>());
+    let (cached, cached_sum) = fastest(|| {
+        let mut table = PawnTable::new();
+        positions.iter().map(|pos| evaluate_cached(pos, &DEFAULT_PARAMS, &mut table) as i64).sum()
+    });
+    assert_eq!(plain_sum, cached_sum);
+
+    println!("evaluation of {} positions", positions.len());
+    println!("  without pawn table  {:>10.2?}", plain);
+    println!("  with pawn table     {:>10.2?}", cached);
+}
+
+/// Search every position of `GAME` in turn. The trees searched are the same either way:
+/// only the time spent on pawn structure differs.
+fn play(keep_tables: bool) {
+    let limits = SearchLimits { depth: Some(SEARCH_DEPTH), ..Default::default() };
+    let tt = TranspositionTable::new(16);
+    let mut pawns = [PawnTable::new()];
+    let mut pos = Position::startpos();
+    for uci in GAME {
+        if !keep_tables {
+            pawns = [PawnTable::new()];
+        }
+        let signals = SearchSignals::default();
+        think(&pos, &limits, &signals, &tt, &mut pawns, None);
+        let mv = *pos.generate_legal_moves().iter().find(|mv| mv.to_string() == uci).unwrap();
+        pos.make_move(mv);
+    }
+}
+
+fn bench_search() {
+    let (fresh, _) = fastest(|| play(false));
+    let (kept, _) = fastest(|| play(true));
+    println!("{} searches to depth {}", GAME.len(), SEARCH_DEPTH);
+    println!("  new pawn tables every move  {:>10.2?}", fresh);
+    println!("  pawn tables kept            {:>10.2?}", kept);
+}
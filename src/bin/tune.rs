@@ -0,0 +1,12 @@
+// src/bin/tune.rs
+
+//! Texel tuner: `cargo run --release --bin tune -- <positions.epd> [options]`
+
+fn main() {
+    chessr::board::magic::init();
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if let Err(msg) = chessr::tune::main(&args) {
+        eprintln!("{}", msg);
+        std::process::exit(1);
+    }
+}
@@ -0,0 +1,132 @@
+// src/board/attacks.rs
+
+//! Precomputed attack tables for the leaper pieces (knight, king and pawn captures),
+//! plus the between/line geometry used for pins and check blocking.
+//! Tables are built at compile time and indexed by the 0..63 square mapping of `BitBoard`.
+
+use super::bitboard::{sides, BitBoard};
+
+/// Knight attacks from each square
+pub const KNIGHT_ATTACKS: [BitBoard; 64] = leaper_table(&[
+    (1, 2),
+    (2, 1),
+    (2, -1),
+    (1, -2),
+    (-1, -2),
+    (-2, -1),
+    (-2, 1),
+    (-1, 2),
+]);
+
+/// King attacks from each square
+pub const KING_ATTACKS: [BitBoard; 64] = leaper_table(&[
+    (0, 1),
+    (1, 1),
+    (1, 0),
+    (1, -1),
+    (0, -1),
+    (-1, -1),
+    (-1, 0),
+    (-1, 1),
+]);
+
+/// Pawn capture attacks from each square: [side][square]
+pub const PAWN_ATTACKS: [[BitBoard; 64]; 2] = {
+    let mut table = [[BitBoard(0); 64]; 2];
+    table[sides::WHITE] = leaper_table(&[(-1, 1), (1, 1)]);
+    table[sides::BLACK] = leaper_table(&[(-1, -1), (1, -1)]);
+    table
+};
+
+/// Knight attacks from a square
+pub fn knight_attacks(square: usize) -> BitBoard {
+    KNIGHT_ATTACKS[square]
+}
+
+/// King attacks from a square
+pub fn king_attacks(square: usize) -> BitBoard {
+    KING_ATTACKS[square]
+}
+
+/// Squares attacked by a pawn of `side` standing on `square`
+pub fn pawn_attacks(side: usize, square: usize) -> BitBoard {
+    PAWN_ATTACKS[side][square]
+}
+
+/// Build a table of all on-board targets reachable by the given (file, rank) offsets
+const fn leaper_table(deltas: &[(i32, i32)]) -> [BitBoard; 64] {
+    let mut table = [BitBoard(0); 64];
+    let mut square = 0;
+    while square < 64 {
+        let file = (square % 8) as i32;
+        let rank = (square / 8) as i32;
+        let mut bits = 0u64;
+        let mut i = 0;
+        while i < deltas.len() {
+            let (df, dr) = deltas[i];
+            let (f, r) = (file + df, rank + dr);
+            if f >= 0 && f < 8 && r >= 0 && r < 8 {
+                bits |= 1u64 << (r * 8 + f);
+            }
+            i += 1;
+        }
+        table[square] = BitBoard(bits);
+        square += 1;
+    }
+    table
+}
+
+/// Squares strictly between two aligned squares: [from][to] (empty if not aligned)
+pub static BETWEEN: [[BitBoard; 64]; 64] = line_tables(false);
+
+/// Full line through two aligned squares, edge to edge: [a][b] (empty if not aligned)
+pub static LINE: [[BitBoard; 64]; 64] = line_tables(true);
+
+/// Squares strictly between two squares on a shared rank, file or diagonal
+pub fn between(a: usize, b: usize) -> BitBoard {
+    BETWEEN[a][b]
+}
+
+/// The rank, file or diagonal through two squares, if they share one
+pub fn line(a: usize, b: usize) -> BitBoard {
+    LINE[a][b]
+}
+
+/// Build the `BETWEEN` table, or the `LINE` table when `full` is set
+const fn line_tables(full: bool) -> [[BitBoard; 64]; 64] {
+    const DIRECTIONS: [(i32, i32); 8] =
+        [(0, 1), (1, 1), (1, 0), (1, -1), (0, -1), (-1, -1), (-1, 0), (-1, 1)];
+    let mut table = [[BitBoard(0); 64]; 64];
+    let mut from = 0;
+    while from < 64 {
+        let mut d = 0;
+        while d < 8 {
+            let (df, dr) = DIRECTIONS[d];
+            // Bits of the whole line through `from` in this direction (both ways)
+            let mut full_line = 1u64 << from;
+            let mut sign = -1;
+            while sign <= 1 {
+                let (mut f, mut r) = ((from % 8) as i32 + sign * df, (from / 8) as i32 + sign * dr);
+                while f >= 0 && f < 8 && r >= 0 && r < 8 {
+                    full_line |= 1u64 << (r * 8 + f);
+                    f += sign * df;
+                    r += sign * dr;
+                }
+                sign += 2;
+            }
+            // Walk outward, recording each target square
+            let mut between = 0u64;
+            let (mut f, mut r) = ((from % 8) as i32 + df, (from / 8) as i32 + dr);
+            while f >= 0 && f < 8 && r >= 0 && r < 8 {
+                let to = (r * 8 + f) as usize;
+                table[from][to] = BitBoard(if full { full_line } else { between });
+                between |= 1u64 << to;
+                f += df;
+                r += dr;
+            }
+            d += 1;
+        }
+        from += 1;
+    }
+    table
+}
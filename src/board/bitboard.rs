@@ -1,5 +1,12 @@
 // src/board/bitboard.rs
 
+use std::ops::{BitAnd, BitAndAssign, BitOr, BitOrAssign, BitXor, BitXorAssign, Not};
+
+use super::attacks::pawn_attacks;
+use super::makemove::Undo;
+#[cfg(feature = "nnue")]
+use crate::eval::nnue::NnueState;
+
 /// Bitboard representation for chess engine
 /// A BitBoard is a 64-bit integer where each bit corresponds to a square on the chessboard.
 /// Bit 0 corresponds to A1, bit 63 corresponds to H8 (little-endian rank-file mapping).
@@ -44,6 +51,68 @@ impl BitBoard {
     pub fn iter(&self) -> BitBoardIterator {
         BitBoardIterator(self.0)
     }
+
+    /// Check if no bit is set
+    pub const fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+
+    /// Index of the least significant set bit (64 if empty)
+    pub const fn lsb(&self) -> usize {
+        self.0.trailing_zeros() as usize
+    }
+}
+
+/// Masks for files and ranks
+pub const FILE_A: BitBoard = BitBoard(0x0101_0101_0101_0101);
+pub const FILE_H: BitBoard = BitBoard(0x8080_8080_8080_8080);
+pub const RANK_1: BitBoard = BitBoard(0x0000_0000_0000_00FF);
+pub const RANK_8: BitBoard = BitBoard(0xFF00_0000_0000_0000);
+
+impl BitAnd for BitBoard {
+    type Output = BitBoard;
+    fn bitand(self, rhs: BitBoard) -> BitBoard {
+        BitBoard(self.0 & rhs.0)
+    }
+}
+
+impl BitOr for BitBoard {
+    type Output = BitBoard;
+    fn bitor(self, rhs: BitBoard) -> BitBoard {
+        BitBoard(self.0 | rhs.0)
+    }
+}
+
+impl BitXor for BitBoard {
+    type Output = BitBoard;
+    fn bitxor(self, rhs: BitBoard) -> BitBoard {
+        BitBoard(self.0 ^ rhs.0)
+    }
+}
+
+impl Not for BitBoard {
+    type Output = BitBoard;
+    fn not(self) -> BitBoard {
+        BitBoard(!self.0)
+    }
+}
+
+impl BitAndAssign for BitBoard {
+    fn bitand_assign(&mut self, rhs: BitBoard) {
+        self.0 &= rhs.0;
+    }
+}
+
+impl BitOrAssign for BitBoard {
+    fn bitor_assign(&mut self, rhs: BitBoard) {
+        self.0 |= rhs.0;
+    }
+}
+
+impl BitXorAssign for BitBoard {
+    fn bitxor_assign(&mut self, rhs: BitBoard) {
+        self.0 ^= rhs.0;
+    }
 }
 
 /// Iterator over set bits in a BitBoard
@@ -79,6 +148,50 @@ pub mod pieces {
     pub const KING: usize = 5;
 }
 
+/// Square indices, matching the little-endian rank-file mapping of `BitBoard`
+#[rustfmt::skip]
+pub mod squares {
+    pub const A1: usize = 0; pub const B1: usize = 1; pub const C1: usize = 2; pub const D1: usize = 3; pub const E1: usize = 4; pub const F1: usize = 5; pub const G1: usize = 6; pub const H1: usize = 7;
+    pub const A2: usize = 8; pub const B2: usize = 9; pub const C2: usize = 10; pub const D2: usize = 11; pub const E2: usize = 12; pub const F2: usize = 13; pub const G2: usize = 14; pub const H2: usize = 15;
+    pub const A3: usize = 16; pub const B3: usize = 17; pub const C3: usize = 18; pub const D3: usize = 19; pub const E3: usize = 20; pub const F3: usize = 21; pub const G3: usize = 22; pub const H3: usize = 23;
+    pub const A4: usize = 24; pub const B4: usize = 25; pub const C4: usize = 26; pub const D4: usize = 27; pub const E4: usize = 28; pub const F4: usize = 29; pub const G4: usize = 30; pub const H4: usize = 31;
+    pub const A5: usize = 32; pub const B5: usize = 33; pub const C5: usize = 34; pub const D5: usize = 35; pub const E5: usize = 36; pub const F5: usize = 37; pub const G5: usize = 38; pub const H5: usize = 39;
+    pub const A6: usize = 40; pub const B6: usize = 41; pub const C6: usize = 42; pub const D6: usize = 43; pub const E6: usize = 44; pub const F6: usize = 45; pub const G6: usize = 46; pub const H6: usize = 47;
+    pub const A7: usize = 48; pub const B7: usize = 49; pub const C7: usize = 50; pub const D7: usize = 51; pub const E7: usize = 52; pub const F7: usize = 53; pub const G7: usize = 54; pub const H7: usize = 55;
+    pub const A8: usize = 56; pub const B8: usize = 57; pub const C8: usize = 58; pub const D8: usize = 59; pub const E8: usize = 60; pub const F8: usize = 61; pub const G8: usize = 62; pub const H8: usize = 63;
+}
+
+/// Castling right flags, combined as a bitmask in `Position::castling_rights`
+pub mod castling {
+    pub const WHITE_KINGSIDE: u8 = 1;
+    pub const WHITE_QUEENSIDE: u8 = 2;
+    pub const BLACK_KINGSIDE: u8 = 4;
+    pub const BLACK_QUEENSIDE: u8 = 8;
+    pub const ALL: u8 = 15;
+}
+
+/// Algebraic name of a square index (0 -> "a1", 63 -> "h8")
+pub fn square_name(square: usize) -> String {
+    let file = (b'a' + (square % 8) as u8) as char;
+    let rank = (b'1' + (square / 8) as u8) as char;
+    format!("{}{}", file, rank)
+}
+
+/// Parse an algebraic square name ("e4") into a square index
+pub fn parse_square(name: &str) -> Option<usize> {
+    let bytes = name.as_bytes();
+    if bytes.len() != 2 {
+        return None;
+    }
+    let file = bytes[0].wrapping_sub(b'a');
+    let rank = bytes[1].wrapping_sub(b'1');
+    if file < 8 && rank < 8 {
+        Some(rank as usize * 8 + file as usize)
+    } else {
+        None
+    }
+}
+
 /// A full chess position represented by bitboards
 #[derive(Clone, Debug)]
 pub struct Position {
@@ -87,6 +200,34 @@ pub struct Position {
 
     /// Bitboards for all pieces of each side
     pub bb_sides: [BitBoard; 2],
+
+    /// Side to move (`sides::WHITE` or `sides::BLACK`)
+    pub side_to_move: usize,
+
+    /// Remaining castling rights as a bitmask of `castling` flags
+    pub castling_rights: u8,
+
+    /// En passant target square, if the last move was a double pawn push
+    pub en_passant: Option<usize>,
+
+    /// Halfmoves since the last capture or pawn move (fifty-move rule)
+    pub halfmove_clock: u32,
+
+    /// Fullmove number, starting at 1 and incremented after Black moves
+    pub fullmove_number: u32,
+
+    /// Zobrist key of the position, updated incrementally by `make_move`
+    pub hash: u64,
+
+    /// Zobrist key of the pawns alone, for the evaluation's pawn cache
+    pub pawn_hash: u64,
+
+    /// State needed to take back each move made with `make_move`
+    pub undo_stack: Vec<Undo>,
+
+    /// Attached network and its accumulators, if evaluating with NNUE
+    #[cfg(feature = "nnue")]
+    pub nnue: Option<NnueState>,
 }
 
 impl Position {
@@ -95,17 +236,143 @@ impl Position {
         Position {
             bb_pieces: [[BitBoard::empty(); 6]; 2],
             bb_sides: [BitBoard::empty(); 2],
+            side_to_move: sides::WHITE,
+            castling_rights: 0,
+            en_passant: None,
+            halfmove_clock: 0,
+            fullmove_number: 1,
+            hash: 0,
+            pawn_hash: 0,
+            undo_stack: Vec::new(),
+            #[cfg(feature = "nnue")]
+            nnue: None,
         }
     }
 
-    /// Update bb_sides from bb_pieces
+    /// Create the standard starting position
+    pub fn startpos() -> Self {
+        let mut pos = Position::new();
+        pos.bb_pieces[sides::WHITE] = [
+            BitBoard(0x0000_0000_0000_FF00),
+            BitBoard(0x0000_0000_0000_0042),
+            BitBoard(0x0000_0000_0000_0024),
+            BitBoard(0x0000_0000_0000_0081),
+            BitBoard(0x0000_0000_0000_0008),
+            BitBoard(0x0000_0000_0000_0010),
+        ];
+        pos.bb_pieces[sides::BLACK] = [
+            BitBoard(0x00FF_0000_0000_0000),
+            BitBoard(0x4200_0000_0000_0000),
+            BitBoard(0x2400_0000_0000_0000),
+            BitBoard(0x8100_0000_0000_0000),
+            BitBoard(0x0800_0000_0000_0000),
+            BitBoard(0x1000_0000_0000_0000),
+        ];
+        pos.castling_rights = castling::ALL;
+        pos.update_sides();
+        pos
+    }
+
+    /// Update bb_sides from bb_pieces.
+    /// Castling rights whose king or rook has left its home square and an en passant
+    /// square without the double-pushed pawn in front of it are dropped, so the rest
+    /// of the state always agrees with the piece placement. The Zobrist keys are recomputed.
     pub fn update_sides(&mut self) {
-        self.bb_sides[sides::WHITE] = BitBoard(0);
-        self.bb_sides[sides::BLACK] = BitBoard(0);
+        self.bb_sides = self.sides_from_pieces();
+        self.castling_rights &= self.supported_castling_rights();
+        if !self.en_passant_supported() {
+            self.en_passant = None;
+        }
+        self.hash = self.compute_hash();
+        self.pawn_hash = self.compute_pawn_hash();
+        #[cfg(feature = "nnue")]
+        self.nnue_reset();
+    }
+
+    /// Side occupancies recomputed from bb_pieces
+    pub fn sides_from_pieces(&self) -> [BitBoard; 2] {
+        let mut bb_sides = [BitBoard(0); 2];
         for piece in 0..6 {
-            self.bb_sides[sides::WHITE].0 |= self.bb_pieces[sides::WHITE][piece].0;
-            self.bb_sides[sides::BLACK].0 |= self.bb_pieces[sides::BLACK][piece].0;
+            bb_sides[sides::WHITE].0 |= self.bb_pieces[sides::WHITE][piece].0;
+            bb_sides[sides::BLACK].0 |= self.bb_pieces[sides::BLACK][piece].0;
         }
+        bb_sides
+    }
+
+    /// Castling rights whose king and rook are still on their home squares
+    pub fn supported_castling_rights(&self) -> u8 {
+        let mut rights = 0;
+        for (flag, side, king_sq, rook_sq) in [
+            (castling::WHITE_KINGSIDE, sides::WHITE, squares::E1, squares::H1),
+            (castling::WHITE_QUEENSIDE, sides::WHITE, squares::E1, squares::A1),
+            (castling::BLACK_KINGSIDE, sides::BLACK, squares::E8, squares::H8),
+            (castling::BLACK_QUEENSIDE, sides::BLACK, squares::E8, squares::A8),
+        ] {
+            if self.bb_pieces[side][pieces::KING].is_set(king_sq)
+                && self.bb_pieces[side][pieces::ROOK].is_set(rook_sq)
+            {
+                rights |= flag;
+            }
+        }
+        rights
+    }
+
+    /// Check that the en passant square (if any) is empty with the double-pushed pawn in front
+    pub fn en_passant_supported(&self) -> bool {
+        match self.en_passant {
+            None => true,
+            Some(ep) => {
+                // The pawn that just double-pushed belongs to the side not to move
+                let them = self.side_to_move ^ 1;
+                let pawn_sq = if them == sides::WHITE { ep + 8 } else { ep - 8 };
+                self.bb_pieces[them][pieces::PAWN].is_set(pawn_sq) && !self.occupied().is_set(ep)
+            }
+        }
+    }
+
+    /// The en passant square, if a pawn of the side to move attacks it. Only such a square
+    /// is part of the Zobrist key, so a position has one key however it was reached and
+    /// whether or not its FEN names the square.
+    pub fn en_passant_capture_square(&self) -> Option<usize> {
+        let us = self.side_to_move;
+        self.en_passant
+            .filter(|&ep| !(pawn_attacks(us ^ 1, ep) & self.bb_pieces[us][pieces::PAWN]).is_empty())
+    }
+
+    /// Bitboard of all occupied squares
+    pub fn occupied(&self) -> BitBoard {
+        BitBoard(self.bb_sides[sides::WHITE].0 | self.bb_sides[sides::BLACK].0)
+    }
+
+    /// Side and piece type on a square, if any
+    pub fn piece_at(&self, square: usize) -> Option<(usize, usize)> {
+        let side = if self.bb_sides[sides::WHITE].is_set(square) {
+            sides::WHITE
+        } else if self.bb_sides[sides::BLACK].is_set(square) {
+            sides::BLACK
+        } else {
+            return None;
+        };
+        (0..6)
+            .find(|&piece| self.bb_pieces[side][piece].is_set(square))
+            .map(|piece| (side, piece))
+    }
+
+    /// Square of the given side's king
+    pub fn king_square(&self, side: usize) -> usize {
+        self.bb_pieces[side][pieces::KING].0.trailing_zeros() as usize
+    }
+
+    /// Check if the side has any piece besides pawns and the king
+    pub fn has_non_pawn_material(&self, side: usize) -> bool {
+        let others = self.bb_sides[side]
+            & !(self.bb_pieces[side][pieces::PAWN] | self.bb_pieces[side][pieces::KING]);
+        !others.is_empty()
     }
 }
 
+impl Default for Position {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,249 @@
+// src/board/fen.rs
+
+//! Forsyth–Edwards Notation parsing and serialization for `Position`
+
+use std::fmt;
+
+use super::bitboard::{castling, parse_square, pieces, sides, square_name, Position};
+
+/// FEN of the standard starting position
+pub const STARTPOS_FEN: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+
+/// Piece letters indexed by piece type (white uses upper case)
+const PIECE_CHARS: [char; 6] = ['p', 'n', 'b', 'r', 'q', 'k'];
+
+/// Error returned when a FEN string cannot be parsed, naming the malformed field
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum FenError {
+    /// A required field is absent (piece placement, side to move, castling or en passant)
+    MissingField(&'static str),
+    /// More than six whitespace separated fields were given
+    TooManyFields,
+    /// The piece placement field is malformed
+    PiecePlacement(String),
+    /// The side to move is not `w` or `b`
+    SideToMove(String),
+    /// The castling field contains something other than `-` or `KQkq`
+    Castling(String),
+    /// The en passant field is not `-` or a square on rank 3 / rank 6
+    EnPassant(String),
+    /// The halfmove clock is not a non-negative integer
+    HalfmoveClock(String),
+    /// The fullmove number is not a positive integer
+    FullmoveNumber(String),
+}
+
+impl fmt::Display for FenError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FenError::MissingField(field) => write!(f, "missing FEN field: {}", field),
+            FenError::TooManyFields => write!(f, "too many FEN fields"),
+            FenError::PiecePlacement(msg) => write!(f, "invalid piece placement: {}", msg),
+            FenError::SideToMove(s) => write!(f, "invalid side to move: '{}'", s),
+            FenError::Castling(s) => write!(f, "invalid castling rights: '{}'", s),
+            FenError::EnPassant(s) => write!(f, "invalid en passant square: '{}'", s),
+            FenError::HalfmoveClock(s) => write!(f, "invalid halfmove clock: '{}'", s),
+            FenError::FullmoveNumber(s) => write!(f, "invalid fullmove number: '{}'", s),
+        }
+    }
+}
+
+impl std::error::Error for FenError {}
+
+impl Position {
+    /// Parse a position from a FEN string.
+    /// The halfmove clock and fullmove number may be omitted (they default to 0 and 1).
+    pub fn from_fen(fen: &str) -> Result<Self, FenError> {
+        let mut fields = fen.split_whitespace();
+        let placement = fields.next().ok_or(FenError::MissingField("piece placement"))?;
+        let side = fields.next().ok_or(FenError::MissingField("side to move"))?;
+        let rights = fields.next().ok_or(FenError::MissingField("castling rights"))?;
+        let ep = fields.next().ok_or(FenError::MissingField("en passant"))?;
+        let halfmove = fields.next();
+        let fullmove = fields.next();
+        if fields.next().is_some() {
+            return Err(FenError::TooManyFields);
+        }
+
+        let mut pos = Position::new();
+        parse_placement(&mut pos, placement)?;
+
+        pos.side_to_move = match side {
+            "w" => sides::WHITE,
+            "b" => sides::BLACK,
+            _ => return Err(FenError::SideToMove(side.to_string())),
+        };
+
+        pos.castling_rights = parse_castling(rights)?;
+
+        pos.en_passant = match ep {
+            "-" => None,
+            _ => {
+                let square = parse_square(ep).ok_or_else(|| FenError::EnPassant(ep.to_string()))?;
+                let expected_rank = if pos.side_to_move == sides::WHITE { 5 } else { 2 };
+                if square / 8 != expected_rank {
+                    return Err(FenError::EnPassant(ep.to_string()));
+                }
+                Some(square)
+            }
+        };
+
+        if let Some(halfmove) = halfmove {
+            pos.halfmove_clock = halfmove
+                .parse()
+                .map_err(|_| FenError::HalfmoveClock(halfmove.to_string()))?;
+        }
+
+        if let Some(fullmove) = fullmove {
+            pos.fullmove_number = match fullmove.parse() {
+                Ok(n) if n >= 1 => n,
+                _ => return Err(FenError::FullmoveNumber(fullmove.to_string())),
+            };
+        }
+
+        // Drops castling rights and en passant squares the placement cannot support
+        pos.update_sides();
+        Ok(pos)
+    }
+
+    /// Serialize the position as a six-field FEN string
+    pub fn to_fen(&self) -> String {
+        let mut fen = String::with_capacity(90);
+
+        for rank in (0..8).rev() {
+            let mut empty = 0;
+            for file in 0..8 {
+                match self.piece_char_at(rank * 8 + file) {
+                    Some(c) => {
+                        if empty > 0 {
+                            fen.push((b'0' + empty) as char);
+                            empty = 0;
+                        }
+                        fen.push(c);
+                    }
+                    None => empty += 1,
+                }
+            }
+            if empty > 0 {
+                fen.push((b'0' + empty) as char);
+            }
+            if rank > 0 {
+                fen.push('/');
+            }
+        }
+
+        fen.push(' ');
+        fen.push(if self.side_to_move == sides::WHITE { 'w' } else { 'b' });
+
+        fen.push(' ');
+        if self.castling_rights == 0 {
+            fen.push('-');
+        } else {
+            for (flag, c) in [
+                (castling::WHITE_KINGSIDE, 'K'),
+                (castling::WHITE_QUEENSIDE, 'Q'),
+                (castling::BLACK_KINGSIDE, 'k'),
+                (castling::BLACK_QUEENSIDE, 'q'),
+            ] {
+                if self.castling_rights & flag != 0 {
+                    fen.push(c);
+                }
+            }
+        }
+
+        fen.push(' ');
+        match self.en_passant {
+            Some(square) => fen.push_str(&square_name(square)),
+            None => fen.push('-'),
+        }
+
+        fen.push_str(&format!(" {} {}", self.halfmove_clock, self.fullmove_number));
+        fen
+    }
+
+    /// FEN letter of the piece on a square, if any
+    fn piece_char_at(&self, square: usize) -> Option<char> {
+        for side in [sides::WHITE, sides::BLACK] {
+            for (piece, bb) in self.bb_pieces[side].iter().enumerate() {
+                if bb.is_set(square) {
+                    let c = PIECE_CHARS[piece];
+                    return Some(if side == sides::WHITE { c.to_ascii_uppercase() } else { c });
+                }
+            }
+        }
+        None
+    }
+}
+
+/// Fill `bb_pieces` and `bb_sides` from the piece placement field
+fn parse_placement(pos: &mut Position, placement: &str) -> Result<(), FenError> {
+    let ranks: Vec<&str> = placement.split('/').collect();
+    if ranks.len() != 8 {
+        return Err(FenError::PiecePlacement(format!("expected 8 ranks, found {}", ranks.len())));
+    }
+
+    // FEN lists rank 8 first
+    for (i, rank_str) in ranks.iter().enumerate() {
+        let rank = 7 - i;
+        let mut file = 0;
+        for c in rank_str.chars() {
+            if let Some(skip) = c.to_digit(10) {
+                if skip == 0 || skip > 8 {
+                    return Err(FenError::PiecePlacement(format!("invalid empty count '{}'", c)));
+                }
+                file += skip as usize;
+            } else {
+                let piece = PIECE_CHARS
+                    .iter()
+                    .position(|&p| p == c.to_ascii_lowercase())
+                    .ok_or_else(|| FenError::PiecePlacement(format!("unknown piece '{}'", c)))?;
+                let side = if c.is_ascii_uppercase() { sides::WHITE } else { sides::BLACK };
+                if file >= 8 {
+                    return Err(FenError::PiecePlacement(format!("rank {} is too long", rank + 1)));
+                }
+                if piece == pieces::PAWN && (rank == 0 || rank == 7) {
+                    return Err(FenError::PiecePlacement(format!("pawn on rank {}", rank + 1)));
+                }
+                pos.bb_pieces[side][piece].set_bit(rank * 8 + file);
+                file += 1;
+            }
+            if file > 8 {
+                return Err(FenError::PiecePlacement(format!("rank {} is too long", rank + 1)));
+            }
+        }
+        if file != 8 {
+            return Err(FenError::PiecePlacement(format!("rank {} is too short", rank + 1)));
+        }
+    }
+
+    for (side, name) in [(sides::WHITE, "white"), (sides::BLACK, "black")] {
+        if pos.bb_pieces[side][pieces::KING].popcount() != 1 {
+            return Err(FenError::PiecePlacement(format!("{} must have exactly one king", name)));
+        }
+    }
+
+    pos.update_sides();
+    Ok(())
+}
+
+/// Parse the castling field into a `castling` bitmask
+fn parse_castling(rights: &str) -> Result<u8, FenError> {
+    if rights == "-" {
+        return Ok(0);
+    }
+    let mut mask = 0;
+    for c in rights.chars() {
+        let flag = match c {
+            'K' => castling::WHITE_KINGSIDE,
+            'Q' => castling::WHITE_QUEENSIDE,
+            'k' => castling::BLACK_KINGSIDE,
+            'q' => castling::BLACK_QUEENSIDE,
+            _ => return Err(FenError::Castling(rights.to_string())),
+        };
+        if mask & flag != 0 {
+            return Err(FenError::Castling(rights.to_string()));
+        }
+        mask |= flag;
+    }
+    Ok(mask)
+}
@@ -0,0 +1,245 @@
+// src/board/makemove.rs
+
+//! Making and taking back moves with incremental bitboard and Zobrist key updates.
+//! `make_move` pushes the irreversible state onto `Position::undo_stack` and
+//! `unmake_move` restores it, so a search can walk the tree on a single position.
+
+use super::attacks::pawn_attacks;
+use super::bitboard::{castling, pieces, sides, squares, BitBoard, Position};
+use super::moves::{flags, Move};
+use super::zobrist::{CASTLING_KEYS, EN_PASSANT_KEYS, PIECE_KEYS, SIDE_KEY};
+
+/// Irreversible state saved before a move is made
+#[derive(Copy, Clone, Debug)]
+pub struct Undo {
+    /// The move that was made
+    pub mv: Move,
+    /// Type of the captured piece, if any
+    pub captured: Option<usize>,
+    /// Castling rights before the move
+    pub castling_rights: u8,
+    /// En passant square before the move
+    pub en_passant: Option<usize>,
+    /// Halfmove clock before the move
+    pub halfmove_clock: u32,
+    /// Zobrist key before the move
+    pub hash: u64,
+    /// Pawn key before the move
+    pub pawn_hash: u64,
+}
+
+/// Castling rights kept when a piece moves from or to each square
+const CASTLING_MASK: [u8; 64] = {
+    let mut mask = [castling::ALL; 64];
+    mask[squares::A1] = castling::ALL & !castling::WHITE_QUEENSIDE;
+    mask[squares::E1] = castling::ALL & !(castling::WHITE_KINGSIDE | castling::WHITE_QUEENSIDE);
+    mask[squares::H1] = castling::ALL & !castling::WHITE_KINGSIDE;
+    mask[squares::A8] = castling::ALL & !castling::BLACK_QUEENSIDE;
+    mask[squares::E8] = castling::ALL & !(castling::BLACK_KINGSIDE | castling::BLACK_QUEENSIDE);
+    mask[squares::H8] = castling::ALL & !castling::BLACK_KINGSIDE;
+    mask
+};
+
+/// Rook origin and destination for a castling king move, by king destination
+pub(crate) fn castling_rook_squares(king_to: usize) -> (usize, usize) {
+    match king_to {
+        squares::G1 => (squares::H1, squares::F1),
+        squares::C1 => (squares::A1, squares::D1),
+        squares::G8 => (squares::H8, squares::F8),
+        _ => (squares::A8, squares::D8),
+    }
+}
+
+impl Position {
+    /// Make a legal move, saving what is needed to take it back
+    pub fn make_move(&mut self, mv: Move) {
+        let us = self.side_to_move;
+        let them = us ^ 1;
+        let from = mv.from();
+        let to = mv.to();
+        let (_, piece) = self.piece_at(from).expect("make_move: no piece on origin square");
+
+        let captured = if mv.is_en_passant() {
+            Some(pieces::PAWN)
+        } else if mv.is_capture() {
+            self.piece_at(to).map(|(_, p)| p)
+        } else {
+            None
+        };
+        let keyed_en_passant = self.en_passant_capture_square();
+
+        self.undo_stack.push(Undo {
+            mv,
+            captured,
+            castling_rights: self.castling_rights,
+            en_passant: self.en_passant,
+            halfmove_clock: self.halfmove_clock,
+            hash: self.hash,
+            pawn_hash: self.pawn_hash,
+        });
+
+        if let Some(victim) = captured {
+            let square = if mv.is_en_passant() { en_passant_victim(us, to) } else { to };
+            self.toggle_piece(them, victim, square);
+        }
+
+        self.toggle_piece(us, piece, from);
+        self.toggle_piece(us, mv.promotion().unwrap_or(piece), to);
+
+        if mv.is_castle() {
+            let (rook_from, rook_to) = castling_rook_squares(to);
+            self.toggle_piece(us, pieces::ROOK, rook_from);
+            self.toggle_piece(us, pieces::ROOK, rook_to);
+        }
+
+        self.hash ^= CASTLING_KEYS[self.castling_rights as usize];
+        self.castling_rights &= CASTLING_MASK[from] & CASTLING_MASK[to];
+        self.hash ^= CASTLING_KEYS[self.castling_rights as usize];
+
+        // The key only holds en passant squares an enemy pawn could actually capture on
+        if let Some(ep) = keyed_en_passant {
+            self.hash ^= EN_PASSANT_KEYS[ep % 8];
+        }
+        self.en_passant = None;
+        if mv.flags() == flags::DOUBLE_PUSH {
+            let ep = (from + to) / 2;
+            self.en_passant = Some(ep);
+            if !(pawn_attacks(us, ep) & self.bb_pieces[them][pieces::PAWN]).is_empty() {
+                self.hash ^= EN_PASSANT_KEYS[ep % 8];
+            }
+        }
+
+        if piece == pieces::PAWN || captured.is_some() {
+            self.halfmove_clock = 0;
+        } else {
+            self.halfmove_clock += 1;
+        }
+        if us == sides::BLACK {
+            self.fullmove_number += 1;
+        }
+        self.side_to_move = them;
+        self.hash ^= SIDE_KEY;
+
+        #[cfg(feature = "nnue")]
+        self.nnue_push(mv, piece, captured);
+
+        #[cfg(debug_assertions)]
+        self.assert_consistent();
+    }
+
+    /// Take back the last move made with `make_move`
+    pub fn unmake_move(&mut self) {
+        let undo = self.undo_stack.pop().expect("unmake_move: no move to take back");
+        let mv = undo.mv;
+        let them = self.side_to_move;
+        let us = them ^ 1;
+        let from = mv.from();
+        let to = mv.to();
+
+        self.side_to_move = us;
+        if us == sides::BLACK {
+            self.fullmove_number -= 1;
+        }
+        self.castling_rights = undo.castling_rights;
+        self.en_passant = undo.en_passant;
+        self.halfmove_clock = undo.halfmove_clock;
+
+        if mv.is_castle() {
+            let (rook_from, rook_to) = castling_rook_squares(to);
+            self.toggle_piece(us, pieces::ROOK, rook_to);
+            self.toggle_piece(us, pieces::ROOK, rook_from);
+        }
+
+        let (_, moved) = self.piece_at(to).expect("unmake_move: no piece on target square");
+        self.toggle_piece(us, moved, to);
+        let piece = if mv.is_promotion() { pieces::PAWN } else { moved };
+        self.toggle_piece(us, piece, from);
+
+        if let Some(victim) = undo.captured {
+            let square = if mv.is_en_passant() { en_passant_victim(us, to) } else { to };
+            self.toggle_piece(them, victim, square);
+        }
+        // Restoring the saved keys undoes every piece toggle above
+        self.hash = undo.hash;
+        self.pawn_hash = undo.pawn_hash;
+
+        #[cfg(feature = "nnue")]
+        self.nnue_pop();
+
+        #[cfg(debug_assertions)]
+        self.assert_consistent();
+    }
+
+    /// Pass the move to the opponent without moving a piece (for null move pruning).
+    /// Must not be called in check; taken back with `unmake_null_move`.
+    pub fn make_null_move(&mut self) {
+        self.undo_stack.push(Undo {
+            mv: Move::NONE,
+            captured: None,
+            castling_rights: self.castling_rights,
+            en_passant: self.en_passant,
+            halfmove_clock: self.halfmove_clock,
+            hash: self.hash,
+            pawn_hash: self.pawn_hash,
+        });
+        if let Some(ep) = self.en_passant_capture_square() {
+            self.hash ^= EN_PASSANT_KEYS[ep % 8];
+        }
+        self.en_passant = None;
+        self.halfmove_clock += 1;
+        self.side_to_move ^= 1;
+        self.hash ^= SIDE_KEY;
+
+        #[cfg(feature = "nnue")]
+        self.nnue_push_null();
+    }
+
+    /// Take back the last null move made with `make_null_move`
+    pub fn unmake_null_move(&mut self) {
+        let undo = self.undo_stack.pop().expect("unmake_null_move: no move to take back");
+        debug_assert!(undo.mv.is_none(), "unmake_null_move: last move was not a null move");
+        self.side_to_move ^= 1;
+        self.en_passant = undo.en_passant;
+        self.halfmove_clock = undo.halfmove_clock;
+        self.hash = undo.hash;
+
+        #[cfg(feature = "nnue")]
+        self.nnue_pop();
+    }
+
+    /// Flip a piece on or off a square in bb_pieces, bb_sides and the Zobrist keys
+    #[inline]
+    fn toggle_piece(&mut self, side: usize, piece: usize, square: usize) {
+        let bit = BitBoard::from_square(square);
+        self.bb_pieces[side][piece] ^= bit;
+        self.bb_sides[side] ^= bit;
+        self.hash ^= PIECE_KEYS[side][piece][square];
+        if piece == pieces::PAWN {
+            self.pawn_hash ^= PIECE_KEYS[side][piece][square];
+        }
+    }
+
+    /// Check the incrementally updated state against a full `update_sides` recompute
+    #[cfg(debug_assertions)]
+    fn assert_consistent(&self) {
+        assert_eq!(self.bb_sides, self.sides_from_pieces(), "bb_sides out of sync");
+        assert!(
+            (self.bb_sides[sides::WHITE] & self.bb_sides[sides::BLACK]).is_empty(),
+            "both sides occupy a square"
+        );
+        assert_eq!(
+            self.castling_rights & !self.supported_castling_rights(),
+            0,
+            "castling rights without king and rook at home"
+        );
+        assert!(self.en_passant_supported(), "en passant square without a pawn");
+        assert_eq!(self.hash, self.compute_hash(), "Zobrist key out of sync");
+        assert_eq!(self.pawn_hash, self.compute_pawn_hash(), "pawn key out of sync");
+    }
+}
+
+/// Square of the pawn removed by an en passant capture landing on `to`
+#[inline]
+pub(crate) fn en_passant_victim(us: usize, to: usize) -> usize {
+    if us == sides::WHITE { to - 8 } else { to + 8 }
+}
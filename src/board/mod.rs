@@ -0,0 +1,22 @@
+// src/board/mod.rs
+
+pub mod attacks;
+pub mod bitboard;
+pub mod fen;
+pub mod magic;
+pub mod makemove;
+pub mod movegen;
+pub mod moves;
+pub mod notation;
+pub mod perft;
+pub mod see;
+pub mod status;
+pub mod zobrist;
+#[cfg(all(feature = "pext", target_arch = "x86_64"))]
+pub mod pext;
+
+pub use bitboard::{castling, pieces, sides, squares, BitBoard, BitBoardIterator, Position};
+pub use fen::FenError;
+pub use moves::{Move, MoveList};
+pub use notation::NotationError;
+pub use status::GameStatus;
@@ -0,0 +1,352 @@
+// src/board/movegen.rs
+
+//! Legal move generation.
+//! Moves are generated legal directly: the king never steps onto an attacked square,
+//! pinned pieces stay on their pin line, and in check only evasions are produced.
+
+use super::attacks::{between, king_attacks, knight_attacks, line, pawn_attacks};
+use super::bitboard::{castling, pieces, sides, squares, BitBoard, Position};
+use super::magic::{bishop_attacks, rook_attacks};
+use super::moves::{flags, Move, MoveList};
+
+/// Promotion flags in generation order (queen first)
+const PROMOTIONS: [u16; 4] = [
+    flags::QUEEN_PROMOTION,
+    flags::KNIGHT_PROMOTION,
+    flags::ROOK_PROMOTION,
+    flags::BISHOP_PROMOTION,
+];
+
+impl Position {
+    /// Generate all legal moves for the side to move
+    pub fn generate_legal_moves(&self) -> MoveList {
+        let mut list = MoveList::new();
+        self.generate(&mut list, true, true);
+        list
+    }
+
+    /// Generate legal captures and promotions (including en passant)
+    pub fn generate_noisy_moves(&self) -> MoveList {
+        let mut list = MoveList::new();
+        self.generate(&mut list, true, false);
+        list
+    }
+
+    /// Generate legal moves that are neither captures nor promotions (including castling)
+    pub fn generate_quiet_moves(&self) -> MoveList {
+        let mut list = MoveList::new();
+        self.generate(&mut list, false, true);
+        list
+    }
+
+    /// Pieces of both sides attacking a square, given an occupancy
+    pub fn attackers_to(&self, square: usize, occupied: BitBoard) -> BitBoard {
+        let [white, black] = &self.bb_pieces;
+        let bishops_queens = white[pieces::BISHOP]
+            | white[pieces::QUEEN]
+            | black[pieces::BISHOP]
+            | black[pieces::QUEEN];
+        let rooks_queens =
+            white[pieces::ROOK] | white[pieces::QUEEN] | black[pieces::ROOK] | black[pieces::QUEEN];
+
+        (pawn_attacks(sides::BLACK, square) & white[pieces::PAWN])
+            | (pawn_attacks(sides::WHITE, square) & black[pieces::PAWN])
+            | (knight_attacks(square) & (white[pieces::KNIGHT] | black[pieces::KNIGHT]))
+            | (king_attacks(square) & (white[pieces::KING] | black[pieces::KING]))
+            | (bishop_attacks(square, occupied) & bishops_queens)
+            | (rook_attacks(square, occupied) & rooks_queens)
+    }
+
+    /// Check if `by` attacks a square, given an occupancy
+    pub fn is_square_attacked(&self, square: usize, by: usize, occupied: BitBoard) -> bool {
+        !(self.attackers_to(square, occupied) & self.bb_sides[by]).is_empty()
+    }
+
+    /// Enemy pieces giving check to the side to move
+    pub fn checkers(&self) -> BitBoard {
+        let us = self.side_to_move;
+        self.attackers_to(self.king_square(us), self.occupied()) & self.bb_sides[us ^ 1]
+    }
+
+    /// Check if the side to move is in check
+    pub fn in_check(&self) -> bool {
+        !self.checkers().is_empty()
+    }
+
+    /// Pieces of `side` that are the only blocker between its king and an enemy slider
+    pub fn pinned(&self, side: usize) -> BitBoard {
+        let ksq = self.king_square(side);
+        let them = &self.bb_pieces[side ^ 1];
+        let snipers = (rook_attacks(ksq, self.bb_sides[side ^ 1])
+            & (them[pieces::ROOK] | them[pieces::QUEEN]))
+            | (bishop_attacks(ksq, self.bb_sides[side ^ 1])
+                & (them[pieces::BISHOP] | them[pieces::QUEEN]));
+
+        let occupied = self.occupied();
+        let mut pinned = BitBoard::empty();
+        for sniper in snipers.iter() {
+            let blockers = between(ksq, sniper) & occupied;
+            if blockers.popcount() == 1 {
+                pinned |= blockers & self.bb_sides[side];
+            }
+        }
+        pinned
+    }
+
+    /// Check if a quiet move (neither capture nor promotion) is legal here, without
+    /// generating the others: the piece is ours, its path is clear, the target empty,
+    /// and the king is safe afterwards. Killers and countermoves come from other
+    /// positions and are checked this way before the quiet moves are generated.
+    pub fn is_legal_quiet(&self, mv: Move) -> bool {
+        let us = self.side_to_move;
+        let (from, to) = (mv.from(), mv.to());
+        let occupied = self.occupied();
+        let Some((side, piece)) = self.piece_at(from) else { return false };
+        if side != us || occupied.is_set(to) || mv.is_capture() || mv.is_promotion() {
+            return false;
+        }
+        if mv.is_castle() {
+            let mut list = MoveList::new();
+            if piece == pieces::KING && !self.in_check() {
+                self.generate_castling(&mut list);
+            }
+            return list.contains(&mv);
+        }
+
+        let reachable = match piece {
+            pieces::PAWN => {
+                let (forward, start_rank, promo_rank) =
+                    if us == sides::WHITE { (8, 1, 7) } else { (-8, 6, 0) };
+                let one = (from as isize + forward) as usize;
+                if mv.is_double_push() {
+                    from / 8 == start_rank
+                        && !occupied.is_set(one)
+                        && to as isize == one as isize + forward
+                } else {
+                    to == one && to / 8 != promo_rank
+                }
+            }
+            _ if mv.is_double_push() => false,
+            pieces::KNIGHT => knight_attacks(from).is_set(to),
+            pieces::BISHOP => bishop_attacks(from, occupied).is_set(to),
+            pieces::ROOK => rook_attacks(from, occupied).is_set(to),
+            pieces::QUEEN => {
+                (bishop_attacks(from, occupied) | rook_attacks(from, occupied)).is_set(to)
+            }
+            _ => king_attacks(from).is_set(to),
+        };
+        if !reachable {
+            return false;
+        }
+
+        let them = us ^ 1;
+        if piece == pieces::KING {
+            return !self.is_square_attacked(to, them, occupied ^ BitBoard::from_square(from));
+        }
+        // A quiet move cannot take the checker, so it has to block a single check
+        let ksq = self.king_square(us);
+        let checkers = self.checkers();
+        match checkers.popcount() {
+            0 => {}
+            1 if between(ksq, checkers.lsb()).is_set(to) => {}
+            _ => return false,
+        }
+        !self.pinned(us).is_set(from) || line(ksq, from).is_set(to)
+    }
+
+    /// Generate legal moves into `list`.
+    /// `noisy` selects captures and promotions, `quiet` selects all other moves.
+    pub(crate) fn generate(&self, list: &mut MoveList, noisy: bool, quiet: bool) {
+        let us = self.side_to_move;
+        let them = us ^ 1;
+        let occupied = self.occupied();
+        let ours = self.bb_sides[us];
+        let theirs = self.bb_sides[them];
+        let ksq = self.king_square(us);
+        let checkers = self.attackers_to(ksq, occupied) & theirs;
+
+        // Destination squares wanted by the caller
+        let mut wanted = BitBoard::empty();
+        if noisy {
+            wanted |= theirs;
+        }
+        if quiet {
+            wanted |= !occupied;
+        }
+
+        // King moves, checked against the occupancy without the king so it cannot
+        // retreat along a checking ray
+        let without_king = occupied ^ BitBoard::from_square(ksq);
+        for to in (king_attacks(ksq) & !ours & wanted).iter() {
+            if !self.is_square_attacked(to, them, without_king) {
+                push_move(list, ksq, to, theirs);
+            }
+        }
+
+        // Only the king can answer a double check
+        if checkers.popcount() > 1 {
+            return;
+        }
+
+        // In check, other pieces must capture the checker or block the ray
+        let check_mask = if checkers.is_empty() {
+            !BitBoard::empty()
+        } else {
+            checkers | between(ksq, checkers.lsb())
+        };
+        let pinned = self.pinned(us);
+        let targets = !ours & wanted & check_mask;
+        let own = &self.bb_pieces[us];
+
+        // A pinned knight can never move
+        for from in (own[pieces::KNIGHT] & !pinned).iter() {
+            for to in (knight_attacks(from) & targets).iter() {
+                push_move(list, from, to, theirs);
+            }
+        }
+
+        for from in (own[pieces::BISHOP] | own[pieces::QUEEN]).iter() {
+            let mut attacks = bishop_attacks(from, occupied) & targets;
+            if pinned.is_set(from) {
+                attacks &= line(ksq, from);
+            }
+            for to in attacks.iter() {
+                push_move(list, from, to, theirs);
+            }
+        }
+
+        for from in (own[pieces::ROOK] | own[pieces::QUEEN]).iter() {
+            let mut attacks = rook_attacks(from, occupied) & targets;
+            if pinned.is_set(from) {
+                attacks &= line(ksq, from);
+            }
+            for to in attacks.iter() {
+                push_move(list, from, to, theirs);
+            }
+        }
+
+        self.generate_pawn_moves(list, noisy, quiet, check_mask, pinned);
+
+        if quiet && checkers.is_empty() {
+            self.generate_castling(list);
+        }
+    }
+
+    /// Pawn pushes, captures, promotions and en passant
+    fn generate_pawn_moves(
+        &self,
+        list: &mut MoveList,
+        noisy: bool,
+        quiet: bool,
+        check_mask: BitBoard,
+        pinned: BitBoard,
+    ) {
+        let us = self.side_to_move;
+        let them = us ^ 1;
+        let occupied = self.occupied();
+        let theirs = self.bb_sides[them];
+        let ksq = self.king_square(us);
+        let (forward, start_rank, promo_rank): (isize, usize, usize) =
+            if us == sides::WHITE { (8, 1, 7) } else { (-8, 6, 0) };
+
+        for from in self.bb_pieces[us][pieces::PAWN].iter() {
+            let allowed = if pinned.is_set(from) {
+                line(ksq, from) & check_mask
+            } else {
+                check_mask
+            };
+
+            // Pushes
+            let to = (from as isize + forward) as usize;
+            if !occupied.is_set(to) {
+                if to / 8 == promo_rank {
+                    // All promotions count as noisy
+                    if noisy && allowed.is_set(to) {
+                        for flag in PROMOTIONS {
+                            list.push(Move::new(from, to, flag));
+                        }
+                    }
+                } else if quiet {
+                    if allowed.is_set(to) {
+                        list.push(Move::new(from, to, flags::QUIET));
+                    }
+                    let to2 = (to as isize + forward) as usize;
+                    if from / 8 == start_rank && !occupied.is_set(to2) && allowed.is_set(to2) {
+                        list.push(Move::new(from, to2, flags::DOUBLE_PUSH));
+                    }
+                }
+            }
+
+            if !noisy {
+                continue;
+            }
+
+            // Captures
+            for to in (pawn_attacks(us, from) & theirs & allowed).iter() {
+                if to / 8 == promo_rank {
+                    for flag in PROMOTIONS {
+                        list.push(Move::new(from, to, flag | flags::CAPTURE));
+                    }
+                } else {
+                    list.push(Move::new(from, to, flags::CAPTURE));
+                }
+            }
+
+            // En passant: simulate the capture, since removing two pawns from a rank
+            // can expose the king in ways the pin mask does not see
+            if let Some(ep) = self.en_passant
+                && pawn_attacks(us, from).is_set(ep)
+            {
+                let captured = (ep as isize - forward) as usize;
+                let after = (occupied
+                    ^ BitBoard::from_square(from)
+                    ^ BitBoard::from_square(captured))
+                    | BitBoard::from_square(ep);
+                let remaining = theirs ^ BitBoard::from_square(captured);
+                if (self.attackers_to(ksq, after) & remaining).is_empty() {
+                    list.push(Move::new(from, ep, flags::EN_PASSANT));
+                }
+            }
+        }
+    }
+
+    /// Castling moves; the caller ensures the side to move is not in check
+    fn generate_castling(&self, list: &mut MoveList) {
+        let us = self.side_to_move;
+        let them = us ^ 1;
+        let occupied = self.occupied();
+        let (king_side, queen_side, king) = if us == sides::WHITE {
+            (castling::WHITE_KINGSIDE, castling::WHITE_QUEENSIDE, squares::E1)
+        } else {
+            (castling::BLACK_KINGSIDE, castling::BLACK_QUEENSIDE, squares::E8)
+        };
+
+        // King side: f and g files empty and not attacked
+        if self.castling_rights & king_side != 0
+            && !occupied.is_set(king + 1)
+            && !occupied.is_set(king + 2)
+            && !self.is_square_attacked(king + 1, them, occupied)
+            && !self.is_square_attacked(king + 2, them, occupied)
+        {
+            list.push(Move::new(king, king + 2, flags::KING_CASTLE));
+        }
+
+        // Queen side: b, c and d files empty, c and d not attacked
+        if self.castling_rights & queen_side != 0
+            && !occupied.is_set(king - 1)
+            && !occupied.is_set(king - 2)
+            && !occupied.is_set(king - 3)
+            && !self.is_square_attacked(king - 1, them, occupied)
+            && !self.is_square_attacked(king - 2, them, occupied)
+        {
+            list.push(Move::new(king, king - 2, flags::QUEEN_CASTLE));
+        }
+    }
+}
+
+/// Push a non-pawn move, flagging it as a capture when it lands on an enemy piece
+#[inline]
+fn push_move(list: &mut MoveList, from: usize, to: usize, theirs: BitBoard) {
+    let flag = if theirs.is_set(to) { flags::CAPTURE } else { flags::QUIET };
+    list.push(Move::new(from, to, flag));
+}
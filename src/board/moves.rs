@@ -0,0 +1,179 @@
+// src/board/moves.rs
+
+//! Compact move representation and a fixed-capacity move list
+
+use std::fmt;
+use std::ops::{Deref, DerefMut};
+
+use super::bitboard::{pieces, square_name};
+
+/// Move flags stored in the upper four bits of a `Move`
+pub mod flags {
+    pub const QUIET: u16 = 0;
+    pub const DOUBLE_PUSH: u16 = 1;
+    pub const KING_CASTLE: u16 = 2;
+    pub const QUEEN_CASTLE: u16 = 3;
+    pub const CAPTURE: u16 = 4;
+    pub const EN_PASSANT: u16 = 5;
+    /// Set on all promotions; the low two bits select knight, bishop, rook or queen
+    pub const PROMOTION: u16 = 8;
+    pub const KNIGHT_PROMOTION: u16 = 8;
+    pub const BISHOP_PROMOTION: u16 = 9;
+    pub const ROOK_PROMOTION: u16 = 10;
+    pub const QUEEN_PROMOTION: u16 = 11;
+    pub const KNIGHT_PROMOTION_CAPTURE: u16 = 12;
+    pub const BISHOP_PROMOTION_CAPTURE: u16 = 13;
+    pub const ROOK_PROMOTION_CAPTURE: u16 = 14;
+    pub const QUEEN_PROMOTION_CAPTURE: u16 = 15;
+}
+
+/// A move packed into 16 bits: from square (bits 0-5), to square (bits 6-11)
+/// and `flags` (bits 12-15)
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Default, Debug)]
+pub struct Move(pub u16);
+
+impl Move {
+    /// The null move (a1a1), used as "no move"
+    pub const NONE: Move = Move(0);
+
+    /// Create a move from its squares and flags
+    pub const fn new(from: usize, to: usize, flags: u16) -> Self {
+        Move(from as u16 | ((to as u16) << 6) | (flags << 12))
+    }
+
+    /// Origin square
+    pub const fn from(self) -> usize {
+        (self.0 & 0x3F) as usize
+    }
+
+    /// Destination square
+    pub const fn to(self) -> usize {
+        ((self.0 >> 6) & 0x3F) as usize
+    }
+
+    /// Move flags (see `flags`)
+    pub const fn flags(self) -> u16 {
+        self.0 >> 12
+    }
+
+    /// Check if this is the null move
+    pub const fn is_none(self) -> bool {
+        self.0 == 0
+    }
+
+    /// Check if the move captures a piece (including en passant)
+    pub const fn is_capture(self) -> bool {
+        self.flags() & flags::CAPTURE != 0
+    }
+
+    /// Check if the move promotes a pawn
+    pub const fn is_promotion(self) -> bool {
+        self.flags() & flags::PROMOTION != 0
+    }
+
+    /// Piece type a pawn promotes to, if this is a promotion
+    pub const fn promotion(self) -> Option<usize> {
+        if self.is_promotion() {
+            Some(pieces::KNIGHT + (self.flags() & 3) as usize)
+        } else {
+            None
+        }
+    }
+
+    /// Check if the move is a castling move (king's move)
+    pub const fn is_castle(self) -> bool {
+        matches!(self.flags(), flags::KING_CASTLE | flags::QUEEN_CASTLE)
+    }
+
+    /// Check if the move is an en passant capture
+    pub const fn is_en_passant(self) -> bool {
+        self.flags() == flags::EN_PASSANT
+    }
+
+    /// Check if the move is a double pawn push
+    pub const fn is_double_push(self) -> bool {
+        self.flags() == flags::DOUBLE_PUSH
+    }
+}
+
+/// Moves print in UCI long algebraic notation (e2e4, e7e8q)
+impl fmt::Display for Move {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_none() {
+            return write!(f, "0000");
+        }
+        write!(f, "{}{}", square_name(self.from()), square_name(self.to()))?;
+        if let Some(piece) = self.promotion() {
+            write!(f, "{}", ['n', 'b', 'r', 'q'][piece - pieces::KNIGHT])?;
+        }
+        Ok(())
+    }
+}
+
+/// Maximum number of moves in any legal chess position is 218
+pub const MAX_MOVES: usize = 256;
+
+/// Fixed-capacity list of moves, stored inline
+#[derive(Clone)]
+pub struct MoveList {
+    moves: [Move; MAX_MOVES],
+    len: usize,
+}
+
+impl MoveList {
+    /// Create an empty move list
+    pub fn new() -> Self {
+        MoveList {
+            moves: [Move::NONE; MAX_MOVES],
+            len: 0,
+        }
+    }
+
+    /// Append a move
+    #[inline]
+    pub fn push(&mut self, mv: Move) {
+        debug_assert!(self.len < MAX_MOVES);
+        self.moves[self.len] = mv;
+        self.len += 1;
+    }
+
+    /// Remove all moves
+    pub fn clear(&mut self) {
+        self.len = 0;
+    }
+}
+
+impl Default for MoveList {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Deref for MoveList {
+    type Target = [Move];
+
+    fn deref(&self) -> &[Move] {
+        &self.moves[..self.len]
+    }
+}
+
+impl DerefMut for MoveList {
+    fn deref_mut(&mut self) -> &mut [Move] {
+        &mut self.moves[..self.len]
+    }
+}
+
+impl<'a> IntoIterator for &'a MoveList {
+    type Item = &'a Move;
+    type IntoIter = std::slice::Iter<'a, Move>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl fmt::Debug for MoveList {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter().map(|mv| mv.to_string())).finish()
+    }
+}
@@ -0,0 +1,199 @@
+// src/board/notation.rs
+
+//! Standard Algebraic Notation (SAN) and long algebraic notation (LAN, as used by UCI).
+//! Moves are written the way PGN wants them. Parsing is lenient: castling with zeros,
+//! promotions without `=` or in lower case, missing or extra check marks and
+//! annotations (`!`, `?`) are all accepted, and LAN may include `-`, `x` or a piece
+//! letter. Both parsers only ever return legal moves.
+
+use std::fmt;
+
+use super::bitboard::{parse_square, pieces, square_name, Position};
+use super::moves::{flags, Move};
+
+/// SAN piece letters indexed by piece type
+const PIECE_LETTERS: [char; 6] = ['P', 'N', 'B', 'R', 'Q', 'K'];
+
+/// Error returned when a move cannot be read in the given position
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum NotationError {
+    /// The text is not a move in the notation
+    Malformed(String),
+    /// No legal move matches the text
+    Illegal(String),
+    /// Several legal moves match the text (SAN without needed disambiguation)
+    Ambiguous(String),
+}
+
+impl fmt::Display for NotationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NotationError::Malformed(s) => write!(f, "malformed move: '{}'", s),
+            NotationError::Illegal(s) => write!(f, "illegal move: '{}'", s),
+            NotationError::Ambiguous(s) => write!(f, "ambiguous move: '{}'", s),
+        }
+    }
+}
+
+impl std::error::Error for NotationError {}
+
+impl Position {
+    /// SAN of a legal move, such as `Nbd7`, `exd6`, `e8=Q+` or `O-O-O#`
+    pub fn to_san(&self, mv: Move) -> String {
+        let mut san = String::new();
+        if mv.is_castle() {
+            san.push_str(if mv.flags() == flags::KING_CASTLE { "O-O" } else { "O-O-O" });
+        } else {
+            let (_, piece) = self.piece_at(mv.from()).expect("to_san: no piece on origin square");
+            if piece == pieces::PAWN {
+                if mv.is_capture() {
+                    san.push((b'a' + (mv.from() % 8) as u8) as char);
+                }
+            } else {
+                san.push(PIECE_LETTERS[piece]);
+                san.push_str(&self.disambiguation(mv, piece));
+            }
+            if mv.is_capture() {
+                san.push('x');
+            }
+            san.push_str(&square_name(mv.to()));
+            if let Some(promoted) = mv.promotion() {
+                san.push('=');
+                san.push(PIECE_LETTERS[promoted]);
+            }
+        }
+
+        let mut after = self.clone();
+        after.make_move(mv);
+        if after.in_check() {
+            san.push(if after.generate_legal_moves().is_empty() { '#' } else { '+' });
+        }
+        san
+    }
+
+    /// Origin file, rank or square needed to tell `mv` apart from other moves of the
+    /// same piece type to the same square: the file when that suffices, else the rank
+    fn disambiguation(&self, mv: Move, piece: usize) -> String {
+        let from = mv.from();
+        let rivals: Vec<usize> = self
+            .generate_legal_moves()
+            .iter()
+            .filter(|other| other.to() == mv.to() && other.from() != from)
+            .filter(|other| self.piece_at(other.from()).is_some_and(|(_, p)| p == piece))
+            .map(|other| other.from())
+            .collect();
+        let name = square_name(from);
+        if rivals.is_empty() {
+            String::new()
+        } else if rivals.iter().all(|&sq| sq % 8 != from % 8) {
+            name[..1].to_string()
+        } else if rivals.iter().all(|&sq| sq / 8 != from / 8) {
+            name[1..].to_string()
+        } else {
+            name
+        }
+    }
+
+    /// Parse a move in SAN. Moves in LAN are accepted as well.
+    pub fn parse_san(&self, text: &str) -> Result<Move, NotationError> {
+        let malformed = || NotationError::Malformed(text.to_string());
+        let san = text.trim().trim_end_matches(['+', '#', '!', '?']);
+        let san = san.strip_suffix("e.p.").unwrap_or(san).trim_end();
+
+        let castling = san.replace('0', "O").to_ascii_uppercase();
+        if castling == "O-O" || castling == "O-O-O" {
+            let flag = if castling == "O-O" { flags::KING_CASTLE } else { flags::QUEEN_CASTLE };
+            let moves = self.generate_legal_moves();
+            return moves
+                .iter()
+                .copied()
+                .find(|mv| mv.flags() == flag)
+                .ok_or_else(|| NotationError::Illegal(text.to_string()));
+        }
+
+        let mut chars: Vec<char> = san.chars().filter(|&c| c != 'x' && c != '-').collect();
+        let piece = match chars.first().and_then(|&c| piece_letter(c)) {
+            Some(piece) => {
+                chars.remove(0);
+                piece
+            }
+            None => pieces::PAWN,
+        };
+
+        // Promotion piece at the end, after `=` or straight after the rank
+        let mut promotion = None;
+        let n = chars.len();
+        if n >= 3
+            && chars[n - 1].is_ascii_alphabetic()
+            && (chars[n - 2].is_ascii_digit() || chars[n - 2] == '=')
+        {
+            match piece_letter(chars[n - 1].to_ascii_uppercase()) {
+                Some(promoted) if promoted != pieces::KING => promotion = Some(promoted),
+                _ => return Err(malformed()),
+            }
+            chars.pop();
+            if chars.last() == Some(&'=') {
+                chars.pop();
+            }
+        }
+
+        if chars.len() < 2 {
+            return Err(malformed());
+        }
+        let target: String = chars[chars.len() - 2..].iter().collect();
+        let to = parse_square(&target).ok_or_else(malformed)?;
+        let mut from_file = None;
+        let mut from_rank = None;
+        for &c in &chars[..chars.len() - 2] {
+            match c {
+                'a'..='h' => from_file = Some(c as usize - 'a' as usize),
+                '1'..='8' => from_rank = Some(c as usize - '1' as usize),
+                _ => return Err(malformed()),
+            }
+        }
+
+        let moves = self.generate_legal_moves();
+        let mut matching = moves.iter().copied().filter(|mv| {
+            mv.to() == to
+                && !mv.is_castle()
+                && mv.promotion() == promotion
+                && from_file.is_none_or(|file| mv.from() % 8 == file)
+                && from_rank.is_none_or(|rank| mv.from() / 8 == rank)
+                && self.piece_at(mv.from()).is_some_and(|(_, p)| p == piece)
+        });
+        match (matching.next(), matching.next()) {
+            (Some(mv), None) => Ok(mv),
+            (Some(_), Some(_)) => Err(NotationError::Ambiguous(text.to_string())),
+            // Moves written in LAN, such as `g1f3` or `e1g1` for castling
+            (None, _) => {
+                self.parse_lan(text).map_err(|_| NotationError::Illegal(text.to_string()))
+            }
+        }
+    }
+
+    /// Parse a move in LAN (`e2e4`, `e7e8q`, also `e2-e4`, `Ng1-f3`, `e7xd8=Q`)
+    pub fn parse_lan(&self, text: &str) -> Result<Move, NotationError> {
+        let mut lan: String = text.trim().chars().filter(|&c| !"-x=+#!?".contains(c)).collect();
+        // A piece letter is only one when a file follows: `B2B4` is a pawn move
+        let mut chars = lan.chars();
+        let first = chars.next().filter(|c| PIECE_LETTERS.contains(c));
+        if first.is_some() && chars.next().is_some_and(|c| c.is_ascii_alphabetic()) {
+            lan.remove(0);
+        }
+        let lan = lan.to_ascii_lowercase();
+        if !(4..=5).contains(&lan.len()) || !lan.is_ascii() {
+            return Err(NotationError::Malformed(text.to_string()));
+        }
+        let moves = self.generate_legal_moves();
+        moves
+            .iter()
+            .copied()
+            .find(|mv| mv.to_string() == lan)
+            .ok_or_else(|| NotationError::Illegal(text.to_string()))
+    }
+}
+
+/// Piece type of an upper case SAN piece letter other than the pawn's
+fn piece_letter(c: char) -> Option<usize> {
+    PIECE_LETTERS[1..].iter().position(|&l| l == c).map(|i| i + 1)
+}
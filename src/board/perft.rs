@@ -0,0 +1,41 @@
+// src/board/perft.rs
+
+//! Perft (performance test) node counting for validating move generation
+
+use super::bitboard::Position;
+use super::moves::Move;
+
+impl Position {
+    /// Count the leaf nodes of the legal move tree to the given depth
+    pub fn perft(&mut self, depth: u32) -> u64 {
+        if depth == 0 {
+            return 1;
+        }
+        let moves = self.generate_legal_moves();
+        // Bulk counting: the number of legal moves is the leaf count one ply up
+        if depth == 1 {
+            return moves.len() as u64;
+        }
+        let mut nodes = 0;
+        for &mv in moves.iter() {
+            self.make_move(mv);
+            nodes += self.perft(depth - 1);
+            self.unmake_move();
+        }
+        nodes
+    }
+
+    /// Perft node counts below each root move
+    pub fn divide(&mut self, depth: u32) -> Vec<(Move, u64)> {
+        let moves = self.generate_legal_moves();
+        moves
+            .iter()
+            .map(|&mv| {
+                self.make_move(mv);
+                let nodes = if depth > 1 { self.perft(depth - 1) } else { 1 };
+                self.unmake_move();
+                (mv, nodes)
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,22 @@
+// src/board/pext.rs
+
+//! BMI2 parallel-bit-extract indexing for slider attacks (`pext` feature, x86_64 only).
+//! PEXT maps every subset of a relevant mask to a dense index of the same size as a
+//! magic index, so it shares the attack table layout of `magic`.
+
+use std::arch::x86_64::_pext_u64;
+
+/// Whether the running CPU supports BMI2
+pub fn available() -> bool {
+    is_x86_feature_detected!("bmi2")
+}
+
+/// Extract the bits of `occupied` selected by `mask` into the low bits of the result.
+///
+/// # Safety
+/// The CPU must support BMI2 (see `available`).
+#[inline]
+#[target_feature(enable = "bmi2")]
+pub unsafe fn pext(occupied: u64, mask: u64) -> u64 {
+    _pext_u64(occupied, mask)
+}
@@ -0,0 +1,107 @@
+// src/board/see.rs
+
+//! Static exchange evaluation: the material outcome of the capture sequence on one
+//! square when both sides always recapture with their least valuable attacker and
+//! may stop capturing whenever that is better for them
+
+use super::bitboard::{pieces, BitBoard, Position};
+use super::magic::{bishop_attacks, rook_attacks};
+use super::moves::Move;
+
+/// Piece values used by the exchange evaluator, by piece type
+pub const SEE_VALUES: [i32; 6] = [100, 320, 330, 500, 900, 20_000];
+
+impl Position {
+    /// Material gain of `mv` for the side making it, after the best exchange sequence
+    /// on its target square. Quiet moves score the loss of the moved piece if it can
+    /// be taken for free, so `see(mv) >= 0` means the move does not lose material.
+    pub fn see(&self, mv: Move) -> i32 {
+        if mv.is_castle() {
+            return 0;
+        }
+        let from = mv.from();
+        let to = mv.to();
+        let us = self.side_to_move;
+        let Some((_, mut moving)) = self.piece_at(from) else {
+            return 0;
+        };
+
+        let mut occupied = self.occupied() ^ BitBoard::from_square(from);
+        let mut gain = [0i32; 32];
+        gain[0] = if mv.is_en_passant() {
+            let victim = if to > from { to - 8 } else { to + 8 };
+            occupied ^= BitBoard::from_square(victim);
+            SEE_VALUES[pieces::PAWN]
+        } else {
+            self.piece_at(to).map_or(0, |(_, p)| SEE_VALUES[p])
+        };
+        if let Some(promoted) = mv.promotion() {
+            gain[0] += SEE_VALUES[promoted] - SEE_VALUES[pieces::PAWN];
+            moving = promoted;
+        }
+
+        let diagonal = self.bb_pieces[0][pieces::BISHOP]
+            | self.bb_pieces[0][pieces::QUEEN]
+            | self.bb_pieces[1][pieces::BISHOP]
+            | self.bb_pieces[1][pieces::QUEEN];
+        let straight = self.bb_pieces[0][pieces::ROOK]
+            | self.bb_pieces[0][pieces::QUEEN]
+            | self.bb_pieces[1][pieces::ROOK]
+            | self.bb_pieces[1][pieces::QUEEN];
+
+        let mut attackers = self.attackers_to(to, occupied) & occupied;
+        // Value of the piece currently standing on the target square
+        let mut on_target = SEE_VALUES[moving];
+        let mut side = us ^ 1;
+        let mut depth = 0;
+
+        loop {
+            let ours = attackers & self.bb_sides[side];
+            if ours.is_empty() {
+                break;
+            }
+            let (square, piece) = self.least_valuable(side, ours);
+
+            // Removing the capturer can uncover sliders behind it (x-rays)
+            occupied ^= BitBoard::from_square(square);
+            if matches!(piece, pieces::PAWN | pieces::BISHOP | pieces::QUEEN) {
+                attackers |= bishop_attacks(to, occupied) & diagonal;
+            }
+            if matches!(piece, pieces::ROOK | pieces::QUEEN) {
+                attackers |= rook_attacks(to, occupied) & straight;
+            }
+            attackers &= occupied;
+
+            // The king may only recapture when nothing defends the square any more
+            if piece == pieces::KING && !(attackers & self.bb_sides[side ^ 1]).is_empty() {
+                break;
+            }
+
+            depth += 1;
+            gain[depth] = on_target - gain[depth - 1];
+            on_target = SEE_VALUES[piece];
+            side ^= 1;
+            if depth == gain.len() - 1 {
+                break;
+            }
+        }
+
+        // Each side may decline to continue the exchange
+        while depth > 0 {
+            gain[depth - 1] = -(-gain[depth - 1]).max(gain[depth]);
+            depth -= 1;
+        }
+        gain[0]
+    }
+
+    /// Square and type of the least valuable piece of `side` within `candidates`
+    fn least_valuable(&self, side: usize, candidates: BitBoard) -> (usize, usize) {
+        for piece in pieces::PAWN..=pieces::KING {
+            let bb = self.bb_pieces[side][piece] & candidates;
+            if !bb.is_empty() {
+                return (bb.lsb(), piece);
+            }
+        }
+        unreachable!("least_valuable called without candidates")
+    }
+}
@@ -0,0 +1,106 @@
+// src/board/status.rs
+
+//! End of game detection: checkmate, stalemate and the draw rules.
+//! The undo stack holds the Zobrist key of every position since the game started (or
+//! since the FEN it was set up from), so repetitions are found by walking it back as
+//! far as the last capture or pawn move.
+
+use super::bitboard::{pieces, BitBoard, Position};
+
+/// Dark squares (a1, c1, ..., h8)
+const DARK_SQUARES: BitBoard = BitBoard(0xAA55_AA55_AA55_AA55);
+
+/// State of the game in a position. Checkmate takes priority over the fifty-move rule;
+/// the draw rules are checked in the order listed.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum GameStatus {
+    Ongoing,
+    Checkmate,
+    Stalemate,
+    /// A hundred halfmoves without a capture or pawn move
+    FiftyMoveRule,
+    /// The same position for the third time
+    ThreefoldRepetition,
+    /// Neither side can ever checkmate
+    InsufficientMaterial,
+}
+
+impl GameStatus {
+    /// Check if the game ended in a draw
+    pub fn is_draw(self) -> bool {
+        !matches!(self, GameStatus::Ongoing | GameStatus::Checkmate)
+    }
+}
+
+impl Position {
+    /// State of the game in this position
+    pub fn status(&self) -> GameStatus {
+        if self.generate_legal_moves().is_empty() {
+            return if self.in_check() { GameStatus::Checkmate } else { GameStatus::Stalemate };
+        }
+        if self.halfmove_clock >= 100 {
+            GameStatus::FiftyMoveRule
+        } else if self.repetitions() >= 3 {
+            GameStatus::ThreefoldRepetition
+        } else if self.has_insufficient_material() {
+            GameStatus::InsufficientMaterial
+        } else {
+            GameStatus::Ongoing
+        }
+    }
+
+    /// Times this position has occurred, counting the current one
+    pub fn repetitions(&self) -> usize {
+        1 + self.earlier_occurrences().count()
+    }
+
+    /// Check for a repetition draw as seen by a search `ply` plies below its root. Any
+    /// repetition inside the search counts, since the side that could avoid it would
+    /// have; positions before the root only count when they make a threefold.
+    pub fn is_repetition_draw(&self, ply: usize) -> bool {
+        let mut count = 0;
+        for distance in self.earlier_occurrences() {
+            count += 1;
+            if distance <= ply || count >= 2 {
+                return true;
+            }
+        }
+        false
+    }
+
+    /// Plies back to each earlier occurrence of this position, nearest first. Only
+    /// positions since the last capture or pawn move can repeat it, and nothing before
+    /// a null move is a real game position.
+    fn earlier_occurrences(&self) -> impl Iterator<Item = usize> + '_ {
+        self.undo_stack
+            .iter()
+            .rev()
+            .take(self.halfmove_clock as usize)
+            .take_while(|undo| !undo.mv.is_none())
+            .enumerate()
+            .filter(|&(i, undo)| i % 2 == 1 && undo.hash == self.hash)
+            .map(|(i, _)| i + 1)
+    }
+
+    /// Check if neither side has the material to checkmate: bare kings, a single minor
+    /// piece, or only bishops all on squares of one colour
+    pub fn has_insufficient_material(&self) -> bool {
+        let [white, black] = &self.bb_pieces;
+        let mating = white[pieces::PAWN]
+            | white[pieces::ROOK]
+            | white[pieces::QUEEN]
+            | black[pieces::PAWN]
+            | black[pieces::ROOK]
+            | black[pieces::QUEEN];
+        if !mating.is_empty() {
+            return false;
+        }
+        let knights = white[pieces::KNIGHT] | black[pieces::KNIGHT];
+        let bishops = white[pieces::BISHOP] | black[pieces::BISHOP];
+        if knights.popcount() + bishops.popcount() <= 1 {
+            return true;
+        }
+        knights.is_empty()
+            && ((bishops & DARK_SQUARES).is_empty() || (bishops & !DARK_SQUARES).is_empty())
+    }
+}
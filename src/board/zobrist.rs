@@ -0,0 +1,105 @@
+// src/board/zobrist.rs
+
+//! Zobrist hashing: a 64-bit position key built by XOR-ing one random key per
+//! feature (piece on square, side to move, castling rights, en passant file).
+//! Keys are generated at compile time from a fixed seed.
+
+use super::bitboard::{pieces, sides, Position};
+
+/// Keys for each piece on each square: [side][piece][square]
+pub const PIECE_KEYS: [[[u64; 64]; 6]; 2] = {
+    let mut keys = [[[0u64; 64]; 6]; 2];
+    let mut state = SEED;
+    let mut side = 0;
+    while side < 2 {
+        let mut piece = 0;
+        while piece < 6 {
+            let mut square = 0;
+            while square < 64 {
+                let (key, next) = splitmix64(state);
+                keys[side][piece][square] = key;
+                state = next;
+                square += 1;
+            }
+            piece += 1;
+        }
+        side += 1;
+    }
+    keys
+};
+
+/// Key XOR-ed in when Black is to move
+pub const SIDE_KEY: u64 = splitmix64(SEED ^ 0x5349_4445).0;
+
+/// Keys for each combination of castling rights (indexed by the `castling` bitmask)
+pub const CASTLING_KEYS: [u64; 16] = {
+    let mut keys = [0u64; 16];
+    let mut state = SEED ^ 0x4341_5354;
+    let mut i = 1;
+    // No rights hashes to zero so an empty mask leaves the key unchanged
+    while i < 16 {
+        let (key, next) = splitmix64(state);
+        keys[i] = key;
+        state = next;
+        i += 1;
+    }
+    keys
+};
+
+/// Keys for the file of the en passant square
+pub const EN_PASSANT_KEYS: [u64; 8] = {
+    let mut keys = [0u64; 8];
+    let mut state = SEED ^ 0x4550_5353;
+    let mut i = 0;
+    while i < 8 {
+        let (key, next) = splitmix64(state);
+        keys[i] = key;
+        state = next;
+        i += 1;
+    }
+    keys
+};
+
+const SEED: u64 = 0x6368_6573_7372_2121;
+
+/// SplitMix64 step: returns (output, next state)
+pub(crate) const fn splitmix64(state: u64) -> (u64, u64) {
+    let next = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    let mut z = next;
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    (z ^ (z >> 31), next)
+}
+
+impl Position {
+    /// Compute the Zobrist key from scratch
+    pub fn compute_hash(&self) -> u64 {
+        let mut hash = 0;
+        for side in [sides::WHITE, sides::BLACK] {
+            for (piece, bb) in self.bb_pieces[side].iter().enumerate() {
+                for square in bb.iter() {
+                    hash ^= PIECE_KEYS[side][piece][square];
+                }
+            }
+        }
+        if self.side_to_move == sides::BLACK {
+            hash ^= SIDE_KEY;
+        }
+        hash ^= CASTLING_KEYS[self.castling_rights as usize];
+        if let Some(ep) = self.en_passant_capture_square() {
+            hash ^= EN_PASSANT_KEYS[ep % 8];
+        }
+        hash
+    }
+
+    /// Compute the pawn key (the piece keys of all pawns) from scratch
+    pub fn compute_pawn_hash(&self) -> u64 {
+        let mut hash = 0;
+        for side in [sides::WHITE, sides::BLACK] {
+            for square in self.bb_pieces[side][pieces::PAWN].iter() {
+                hash ^= PIECE_KEYS[side][pieces::PAWN][square];
+            }
+        }
+        hash
+    }
+}
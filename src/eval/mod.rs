@@ -0,0 +1,162 @@
+// src/eval/mod.rs
+
+//! Hand-crafted evaluation.
+//! Terms are summed as middlegame/endgame `Score` pairs for each side and blended by the
+//! game phase, which falls from `MAX_PHASE` to 0 as minor and major pieces come off.
+
+#[cfg(feature = "nnue")]
+pub mod nnue;
+pub mod params;
+pub mod pawns;
+pub mod score;
+pub mod weights;
+
+pub use params::EvalParams;
+pub use pawns::{PawnEntry, PawnTable};
+pub use score::{Score, Weight, MAX_PHASE};
+pub use weights::DEFAULT_PARAMS;
+
+use crate::board::attacks::{king_attacks, knight_attacks};
+use crate::board::magic::{bishop_attacks, rook_attacks};
+use crate::board::{pieces, sides, BitBoard, Position};
+use pawns::relative_rank;
+
+/// Phase contribution of each piece type
+const PHASE_WEIGHTS: [i32; 6] = [0, 1, 1, 2, 4, 0];
+
+/// Evaluate a position with the default weights, in centipawns from the side to move's
+/// point of view
+pub fn evaluate(pos: &Position) -> i32 {
+    evaluate_with(pos, &DEFAULT_PARAMS)
+}
+
+/// Evaluate a position with the given weights, from the side to move's point of view
+pub fn evaluate_with(pos: &Position, params: &EvalParams) -> i32 {
+    let mut pawns = PawnEntry::new(pos, params);
+    evaluate_entry(pos, params, &mut pawns)
+}
+
+/// Evaluate a position, taking the pawn structure terms from `table`
+pub fn evaluate_cached(pos: &Position, params: &EvalParams, table: &mut PawnTable) -> i32 {
+    evaluate_entry(pos, params, table.probe(pos, params))
+}
+
+fn evaluate_entry(pos: &Position, params: &EvalParams, pawns: &mut PawnEntry) -> i32 {
+    let white = evaluate_terms(pos, params, pawns).taper(game_phase(pos));
+    if pos.side_to_move == sides::WHITE { white } else { -white }
+}
+
+/// Sum of all terms from White's point of view, before tapering
+pub fn evaluate_terms<T: Weight>(
+    pos: &Position,
+    params: &EvalParams<T>,
+    pawns: &mut PawnEntry<T>,
+) -> T {
+    let mut score = pawns.score.clone()
+        + evaluate_side(pos, params, pawns, sides::WHITE)
+        - evaluate_side(pos, params, pawns, sides::BLACK);
+    if pos.side_to_move == sides::WHITE {
+        score += params.tempo.clone();
+    } else {
+        score -= params.tempo.clone();
+    }
+    score
+}
+
+/// Game phase: `MAX_PHASE` with all pieces on the board, 0 with only kings and pawns
+pub fn game_phase(pos: &Position) -> i32 {
+    let mut phase = 0;
+    for side in [sides::WHITE, sides::BLACK] {
+        for (piece, weight) in PHASE_WEIGHTS.iter().enumerate() {
+            phase += weight * pos.bb_pieces[side][piece].popcount() as i32;
+        }
+    }
+    phase.min(MAX_PHASE)
+}
+
+/// All piece terms for one side
+fn evaluate_side<T: Weight>(
+    pos: &Position,
+    params: &EvalParams<T>,
+    pawns: &mut PawnEntry<T>,
+    side: usize,
+) -> T {
+    material(pos, params, side)
+        + pieces_and_mobility(pos, params, pawns, side)
+        + pawns.king_shield(pos, params, side)
+}
+
+/// Material, piece-square tables and the bishop pair
+fn material<T: Weight>(pos: &Position, params: &EvalParams<T>, side: usize) -> T {
+    let flip = if side == sides::WHITE { 56 } else { 0 };
+    let mut score = T::zero();
+    for (piece, bb) in pos.bb_pieces[side].iter().enumerate() {
+        for square in bb.iter() {
+            score += params.material[piece].clone() + params.pst[piece][square ^ flip].clone();
+        }
+    }
+    if pos.bb_pieces[side][pieces::BISHOP].popcount() >= 2 {
+        score += params.bishop_pair.clone();
+    }
+    score
+}
+
+/// Mobility over squares not held by own pieces or attacked by enemy pawns, outposts,
+/// rooks on open files, and attacks on the squares around the enemy king
+fn pieces_and_mobility<T: Weight>(
+    pos: &Position,
+    params: &EvalParams<T>,
+    pawns: &PawnEntry<T>,
+    side: usize,
+) -> T {
+    let own = &pos.bb_pieces[side];
+    let occupied = pos.occupied();
+    let area = !pos.bb_sides[side] & !pawns.attacks[side ^ 1];
+    let enemy_king = pos.king_square(side ^ 1);
+    let king_zone = king_attacks(enemy_king) | BitBoard::from_square(enemy_king);
+    // Squares on the enemy half no enemy pawn can chase a piece from, defended by a pawn
+    let outposts = pawns.weak[side ^ 1] & pawns.attacks[side];
+
+    let mut score = T::zero();
+    for piece in [pieces::KNIGHT, pieces::BISHOP, pieces::ROOK, pieces::QUEEN] {
+        for square in own[piece].iter() {
+            let attacks = match piece {
+                pieces::KNIGHT => knight_attacks(square),
+                pieces::BISHOP => bishop_attacks(square, occupied),
+                pieces::ROOK => rook_attacks(square, occupied),
+                _ => bishop_attacks(square, occupied) | rook_attacks(square, occupied),
+            };
+            let count = (attacks & area).popcount() as usize;
+            score += match piece {
+                pieces::KNIGHT => params.knight_mobility[count].clone(),
+                pieces::BISHOP => params.bishop_mobility[count].clone(),
+                pieces::ROOK => params.rook_mobility[count].clone(),
+                _ => params.queen_mobility[count].clone(),
+            };
+            let king_attacks = (attacks & king_zone).popcount() as i32;
+            if king_attacks > 0 {
+                score += params.king_attack[piece].clone() * king_attacks;
+            }
+
+            let outpost_rank = (3..=5).contains(&relative_rank(side, square));
+            if outpost_rank && outposts.is_set(square) {
+                match piece {
+                    pieces::KNIGHT => score += params.knight_outpost.clone(),
+                    pieces::BISHOP => score += params.bishop_outpost.clone(),
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    for square in own[pieces::ROOK].iter() {
+        if pawns.open_files[side].is_set(square) {
+            score += if pawns.open_files[side ^ 1].is_set(square) {
+                params.rook_open_file.clone()
+            } else {
+                params.rook_semi_open_file.clone()
+            };
+        }
+    }
+    score
+}
@@ -0,0 +1,259 @@
+// src/eval/nnue/accumulator.rs
+
+//! Accumulators kept alongside a `Position`.
+//! `make_move` pushes an entry holding the piece-square deltas of the move and
+//! `unmake_move` pops it. Entries are brought up to date lazily when a position is
+//! evaluated: from the nearest computed ancestor by applying the deltas in order, or
+//! from scratch when the king of that perspective has changed bucket on the way.
+
+use std::sync::Arc;
+
+use super::network::Network;
+use super::simd;
+use crate::board::makemove::{castling_rook_squares, en_passant_victim};
+use crate::board::{pieces, sides, Move, Position};
+
+/// Most pieces one move adds or removes (castling, or a capturing promotion)
+const MAX_DELTAS: usize = 4;
+
+/// A piece of `side` appearing on (`added`) or leaving `square`
+#[derive(Copy, Clone, Debug, Default)]
+pub struct Delta {
+    pub side: usize,
+    pub piece: usize,
+    pub square: usize,
+    pub added: bool,
+}
+
+#[derive(Clone, Debug)]
+struct Accumulator {
+    /// Hidden layer before activation, by perspective
+    values: [Vec<i16>; 2],
+    /// Whether `values` is up to date, by perspective
+    computed: [bool; 2],
+    /// Set when the perspective's king changed bucket with this move
+    refresh: [bool; 2],
+    /// King squares after the move
+    kings: [usize; 2],
+    deltas: [Delta; MAX_DELTAS],
+    len: usize,
+}
+
+/// Network and accumulator stack of one position
+#[derive(Clone, Debug)]
+pub struct NnueState {
+    net: Arc<Network>,
+    stack: Vec<Accumulator>,
+    /// Entries in use; the last one belongs to the current position
+    len: usize,
+}
+
+impl NnueState {
+    fn new(net: Arc<Network>, kings: [usize; 2]) -> Self {
+        let mut state = NnueState {
+            net,
+            stack: Vec::new(),
+            len: 0,
+        };
+        state.reset(kings);
+        state
+    }
+
+    /// Start over from a single root entry that needs a full refresh
+    fn reset(&mut self, kings: [usize; 2]) {
+        self.len = 0;
+        let entry = self.next_entry();
+        entry.computed = [false; 2];
+        entry.kings = kings;
+        entry.len = 0;
+    }
+
+    /// Push the entry of a child position reached with `deltas`
+    fn push(&mut self, deltas: &[Delta], kings: [usize; 2]) {
+        let parent_kings = self.stack[self.len - 1].kings;
+        let refresh = [sides::WHITE, sides::BLACK].map(|side| {
+            self.net.bucket(side, kings[side]) != self.net.bucket(side, parent_kings[side])
+        });
+        let entry = self.next_entry();
+        entry.computed = [false; 2];
+        entry.refresh = refresh;
+        entry.kings = kings;
+        entry.deltas[..deltas.len()].copy_from_slice(deltas);
+        entry.len = deltas.len();
+    }
+
+    /// Drop the current entry; the root entry is only invalidated
+    fn pop(&mut self) {
+        if self.len > 1 {
+            self.len -= 1;
+        } else {
+            self.stack[0].computed = [false; 2];
+        }
+    }
+
+    /// Claim the entry above the current one, allocating it on first use
+    fn next_entry(&mut self) -> &mut Accumulator {
+        if self.len == self.stack.len() {
+            let hidden = self.net.hidden;
+            self.stack.push(Accumulator {
+                values: [vec![0; hidden], vec![0; hidden]],
+                computed: [false; 2],
+                refresh: [false; 2],
+                kings: [0; 2],
+                deltas: [Delta::default(); MAX_DELTAS],
+                len: 0,
+            });
+        }
+        self.len += 1;
+        &mut self.stack[self.len - 1]
+    }
+
+    /// Bring the current entry of `perspective` up to date
+    fn update(&mut self, pos: &Position, perspective: usize) {
+        let top = self.len - 1;
+        if self.stack[top].computed[perspective] {
+            return;
+        }
+
+        // Walk back to a computed entry without crossing a king bucket change
+        let mut base = top;
+        while base > 0
+            && !self.stack[base].computed[perspective]
+            && !self.stack[base].refresh[perspective]
+        {
+            base -= 1;
+        }
+        if !self.stack[base].computed[perspective] {
+            self.refresh(pos, perspective);
+            return;
+        }
+
+        let net = &*self.net;
+        for i in base + 1..=top {
+            let (done, rest) = self.stack.split_at_mut(i);
+            let (parent, entry) = (&done[i - 1], &mut rest[0]);
+            entry.values[perspective].copy_from_slice(&parent.values[perspective]);
+            let bucket = net.bucket(perspective, entry.kings[perspective]);
+            for delta in &entry.deltas[..entry.len] {
+                let feature = net.feature(perspective, bucket, delta.side, delta.piece, delta.square);
+                let row = net.row(feature);
+                if delta.added {
+                    simd::add(&mut entry.values[perspective], row);
+                } else {
+                    simd::sub(&mut entry.values[perspective], row);
+                }
+            }
+            entry.computed[perspective] = true;
+        }
+    }
+
+    /// Recompute the current entry of `perspective` from all pieces on the board
+    fn refresh(&mut self, pos: &Position, perspective: usize) {
+        let net = &*self.net;
+        let entry = &mut self.stack[self.len - 1];
+        let values = &mut entry.values[perspective];
+        values.copy_from_slice(&net.feature_biases);
+        let bucket = net.bucket(perspective, pos.king_square(perspective));
+        for side in [sides::WHITE, sides::BLACK] {
+            for (piece, bb) in pos.bb_pieces[side].iter().enumerate() {
+                for square in bb.iter() {
+                    let feature = net.feature(perspective, bucket, side, piece, square);
+                    simd::add(values, net.row(feature));
+                }
+            }
+        }
+        entry.computed[perspective] = true;
+    }
+
+    /// Network output for the current entry, in centipawns from the side to move's
+    /// point of view
+    fn evaluate(&mut self, pos: &Position) -> i32 {
+        self.update(pos, sides::WHITE);
+        self.update(pos, sides::BLACK);
+
+        let net = &*self.net;
+        let us = pos.side_to_move;
+        let entry = &self.stack[self.len - 1];
+        let (ours, theirs) = net.output_weights.split_at(net.hidden);
+        let qa = net.qa as i16;
+        let output = simd::crelu_dot(&entry.values[us], ours, qa) as i64
+            + simd::crelu_dot(&entry.values[us ^ 1], theirs, qa) as i64
+            + net.output_bias as i64;
+        (output * net.scale as i64 / (net.qa as i64 * net.qb as i64)) as i32
+    }
+}
+
+impl Position {
+    /// Attach a network to evaluate this position with, or detach it with `None`
+    pub fn set_network(&mut self, net: Option<Arc<Network>>) {
+        self.nnue = net.map(|net| NnueState::new(net, self.king_squares()));
+    }
+
+    /// NNUE evaluation from the side to move's point of view, if a network is attached
+    pub fn nnue_evaluate(&mut self) -> Option<i32> {
+        let mut state = self.nnue.take()?;
+        let score = state.evaluate(self);
+        self.nnue = Some(state);
+        Some(score)
+    }
+
+    /// Record the piece-square deltas of a move just made
+    pub(crate) fn nnue_push(&mut self, mv: Move, piece: usize, captured: Option<usize>) {
+        if self.nnue.is_none() {
+            return;
+        }
+        let us = self.side_to_move ^ 1;
+        let them = us ^ 1;
+        let (from, to) = (mv.from(), mv.to());
+        let mut deltas = [Delta::default(); MAX_DELTAS];
+        let mut len = 0;
+        let mut record = |side, piece, square, added| {
+            deltas[len] = Delta { side, piece, square, added };
+            len += 1;
+        };
+
+        record(us, piece, from, false);
+        record(us, mv.promotion().unwrap_or(piece), to, true);
+        if let Some(victim) = captured {
+            let square = if mv.is_en_passant() { en_passant_victim(us, to) } else { to };
+            record(them, victim, square, false);
+        }
+        if mv.is_castle() {
+            let (rook_from, rook_to) = castling_rook_squares(to);
+            record(us, pieces::ROOK, rook_from, false);
+            record(us, pieces::ROOK, rook_to, true);
+        }
+
+        let kings = self.king_squares();
+        if let Some(state) = &mut self.nnue {
+            state.push(&deltas[..len], kings);
+        }
+    }
+
+    /// Record a null move, which changes no piece
+    pub(crate) fn nnue_push_null(&mut self) {
+        let kings = self.king_squares();
+        if let Some(state) = &mut self.nnue {
+            state.push(&[], kings);
+        }
+    }
+
+    /// Drop the accumulator entry of the move being taken back
+    pub(crate) fn nnue_pop(&mut self) {
+        if let Some(state) = &mut self.nnue {
+            state.pop();
+        }
+    }
+
+    /// Invalidate the accumulators after the board was changed wholesale
+    pub(crate) fn nnue_reset(&mut self) {
+        let kings = self.king_squares();
+        if let Some(state) = &mut self.nnue {
+            state.reset(kings);
+        }
+    }
+
+    fn king_squares(&self) -> [usize; 2] {
+        [sides::WHITE, sides::BLACK].map(|side| self.bb_pieces[side][pieces::KING].lsb())
+    }
+}
@@ -0,0 +1,13 @@
+// src/eval/nnue/mod.rs
+
+//! Efficiently updatable neural network evaluation (`nnue` feature).
+//! A network is loaded from a file (see `network` for the format) and attached to a
+//! `Position` with `Position::set_network`; the position then keeps its accumulators up
+//! to date through `make_move` and `unmake_move`.
+
+pub mod accumulator;
+pub mod network;
+pub mod simd;
+
+pub use accumulator::NnueState;
+pub use network::{Network, NetworkError};
@@ -0,0 +1,250 @@
+// src/eval/nnue/network.rs
+
+//! NNUE network file format and loader.
+//!
+//! No network ships with the crate: a trainer writes one in the `CRNN` format below and
+//! the engine loads it with the `EvalFile` option. Everything is little-endian, with no
+//! padding between fields, and the file must end right after the output bias.
+//!
+//! The network is a HalfKA-style perceptron: each perspective sees one input feature per
+//! (king bucket, piece colour relative to the perspective, piece type, square), 768 per
+//! bucket, feeding an accumulator of `hidden` neurons. The output layer takes both
+//! accumulators, side to move first, through a clipped ReLU.
+//!
+//! Header (92 bytes):
+//!
+//! | field      | type    | notes                                                    |
+//! |------------|---------|----------------------------------------------------------|
+//! | magic      | 4 bytes | `CRNN`                                                   |
+//! | version    | u32     | 1                                                        |
+//! | hidden     | u32     | accumulator width, a multiple of 16 up to 4096           |
+//! | buckets    | u32     | number of king buckets, 1 to 64                          |
+//! | qa         | i32     | clipped ReLU ceiling, 1 to 32767                         |
+//! | qb         | i32     | output weight scale, positive                            |
+//! | scale      | i32     | centipawns per unit of network output, positive          |
+//! | bucket map | 64 x u8 | king bucket by square, each below `buckets`              |
+//!
+//! Feature transformer:
+//!
+//! | field           | type                         | notes                               |
+//! |-----------------|------------------------------|-------------------------------------|
+//! | feature weights | buckets x 768 x hidden x i16 | one row of `hidden` per feature     |
+//! | feature biases  | hidden x i16                 | starting value of every accumulator |
+//!
+//! Output layer:
+//!
+//! | field          | type             | notes                                          |
+//! |----------------|------------------|------------------------------------------------|
+//! | output weights | 2 x hidden x i16 | side to move's half first                      |
+//! | output bias    | i32              |                                                |
+//!
+//! Squares (king and piece) are seen from each perspective's own side: Black's
+//! perspective flips them vertically (`square ^ 56`), so a symmetric network scores
+//! mirrored positions alike. The bucket map is indexed the same way. Feature rows are
+//! stored in index order, a feature's index being
+//! `bucket * 768 + (relative colour * 6 + piece type) * 64 + square`, relative colour 0
+//! being the perspective's own pieces and piece types numbered as in `pieces`. The
+//! evaluation is
+//! `(sum(crelu(us) * w_us) + sum(crelu(them) * w_them) + output bias) * scale / (qa * qb)`.
+
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+use crate::board::sides;
+
+const MAGIC: &[u8; 4] = b"CRNN";
+const VERSION: u32 = 1;
+
+/// Input features per king bucket (2 colours x 6 piece types x 64 squares)
+pub const FEATURES_PER_BUCKET: usize = 768;
+
+/// Widest accepted accumulator
+const MAX_HIDDEN: usize = 4096;
+
+/// Error returned when a network file cannot be used
+#[derive(Debug)]
+pub enum NetworkError {
+    /// The file could not be read
+    Io(std::io::Error),
+    /// The file does not start with `CRNN`
+    BadMagic,
+    /// The format version is not supported
+    UnsupportedVersion(u32),
+    /// A header field is out of range
+    BadHeader(&'static str),
+    /// The file is shorter or longer than its header implies
+    WrongSize { expected: usize, actual: usize },
+}
+
+impl fmt::Display for NetworkError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NetworkError::Io(err) => write!(f, "cannot read network: {}", err),
+            NetworkError::BadMagic => write!(f, "not a chessr network file"),
+            NetworkError::UnsupportedVersion(v) => write!(f, "unsupported network version {}", v),
+            NetworkError::BadHeader(field) => write!(f, "invalid network header field: {}", field),
+            NetworkError::WrongSize { expected, actual } => {
+                write!(f, "network file has {} bytes, expected {}", actual, expected)
+            }
+        }
+    }
+}
+
+impl std::error::Error for NetworkError {}
+
+/// A loaded network
+pub struct Network {
+    pub hidden: usize,
+    pub qa: i32,
+    pub qb: i32,
+    pub scale: i32,
+    /// King bucket by square, from the perspective's own side
+    pub bucket_map: [usize; 64],
+    /// `buckets * FEATURES_PER_BUCKET` rows of `hidden` weights
+    pub feature_weights: Vec<i16>,
+    pub feature_biases: Vec<i16>,
+    /// Side to move's `hidden` weights, then the other side's
+    pub output_weights: Vec<i16>,
+    pub output_bias: i32,
+}
+
+impl fmt::Debug for Network {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Network")
+            .field("hidden", &self.hidden)
+            .field("buckets", &self.buckets())
+            .finish_non_exhaustive()
+    }
+}
+
+impl Network {
+    /// Load a network file
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, NetworkError> {
+        let bytes = fs::read(path).map_err(NetworkError::Io)?;
+        Network::from_bytes(&bytes)
+    }
+
+    /// Parse a network from the bytes of a network file
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, NetworkError> {
+        let mut reader = Reader { bytes, pos: 0 };
+        if reader.take(4) != Some(MAGIC.as_slice()) {
+            return Err(NetworkError::BadMagic);
+        }
+        let header = |value: Option<u32>| value.ok_or(NetworkError::BadHeader("truncated"));
+        let version = header(reader.u32())?;
+        if version != VERSION {
+            return Err(NetworkError::UnsupportedVersion(version));
+        }
+        let hidden = header(reader.u32())? as usize;
+        if hidden == 0 || hidden > MAX_HIDDEN || !hidden.is_multiple_of(16) {
+            return Err(NetworkError::BadHeader("hidden"));
+        }
+        let buckets = header(reader.u32())? as usize;
+        if !(1..=64).contains(&buckets) {
+            return Err(NetworkError::BadHeader("buckets"));
+        }
+        let qa = header(reader.u32())? as i32;
+        if !(1..=i16::MAX as i32).contains(&qa) {
+            return Err(NetworkError::BadHeader("qa"));
+        }
+        let qb = header(reader.u32())? as i32;
+        if qb <= 0 {
+            return Err(NetworkError::BadHeader("qb"));
+        }
+        let scale = header(reader.u32())? as i32;
+        if scale <= 0 {
+            return Err(NetworkError::BadHeader("scale"));
+        }
+        let map = reader.take(64).ok_or(NetworkError::BadHeader("truncated"))?;
+        if map.iter().any(|&bucket| bucket as usize >= buckets) {
+            return Err(NetworkError::BadHeader("bucket map"));
+        }
+        let bucket_map = std::array::from_fn(|square| map[square] as usize);
+
+        let expected = reader.pos
+            + 2 * (buckets * FEATURES_PER_BUCKET * hidden + hidden + 2 * hidden)
+            + 4;
+        if bytes.len() != expected {
+            return Err(NetworkError::WrongSize { expected, actual: bytes.len() });
+        }
+        let feature_weights = reader.i16s(buckets * FEATURES_PER_BUCKET * hidden);
+        let feature_biases = reader.i16s(hidden);
+        let output_weights = reader.i16s(2 * hidden);
+        let output_bias = reader.u32().unwrap_or_default() as i32;
+
+        Ok(Network {
+            hidden,
+            qa,
+            qb,
+            scale,
+            bucket_map,
+            feature_weights,
+            feature_biases,
+            output_weights,
+            output_bias,
+        })
+    }
+
+    /// Number of king buckets
+    pub fn buckets(&self) -> usize {
+        self.feature_weights.len() / (FEATURES_PER_BUCKET * self.hidden)
+    }
+
+    /// King bucket of `perspective` with its king on `king`
+    #[inline]
+    pub fn bucket(&self, perspective: usize, king: usize) -> usize {
+        self.bucket_map[orient(perspective, king)]
+    }
+
+    /// Index of the feature for a piece of `side` on `square`, seen by `perspective`
+    /// from king bucket `bucket`
+    #[inline]
+    pub fn feature(
+        &self,
+        perspective: usize,
+        bucket: usize,
+        side: usize,
+        piece: usize,
+        square: usize,
+    ) -> usize {
+        let colour = (side != perspective) as usize;
+        bucket * FEATURES_PER_BUCKET + (colour * 6 + piece) * 64 + orient(perspective, square)
+    }
+
+    /// Accumulator weights of a feature
+    #[inline]
+    pub fn row(&self, feature: usize) -> &[i16] {
+        &self.feature_weights[feature * self.hidden..(feature + 1) * self.hidden]
+    }
+}
+
+/// A square as seen from `perspective`'s side of the board
+#[inline]
+fn orient(perspective: usize, square: usize) -> usize {
+    if perspective == sides::WHITE { square } else { square ^ 56 }
+}
+
+/// Little-endian cursor over the file bytes
+struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Option<&'a [u8]> {
+        let slice = self.bytes.get(self.pos..self.pos + len)?;
+        self.pos += len;
+        Some(slice)
+    }
+
+    fn u32(&mut self) -> Option<u32> {
+        self.take(4).map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+    }
+
+    /// Read `len` values; the caller has checked the length of the file
+    fn i16s(&mut self, len: usize) -> Vec<i16> {
+        let bytes = self.take(2 * len).unwrap_or_default();
+        bytes.chunks_exact(2).map(|b| i16::from_le_bytes([b[0], b[1]])).collect()
+    }
+}
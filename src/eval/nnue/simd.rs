@@ -0,0 +1,118 @@
+// src/eval/nnue/simd.rs
+
+//! Vector kernels for the accumulator and output layer.
+//! On x86_64 CPUs with AVX2 (detected at run time) sixteen `i16` lanes are processed
+//! at once; everywhere else the `scalar` versions run. Both give identical results.
+//! Slice lengths must be equal and a multiple of 16.
+
+/// Add a feature row to an accumulator
+#[inline]
+pub fn add(acc: &mut [i16], row: &[i16]) {
+    #[cfg(target_arch = "x86_64")]
+    if is_x86_feature_detected!("avx2") {
+        // SAFETY: AVX2 support was just checked
+        return unsafe { avx2::add(acc, row) };
+    }
+    scalar::add(acc, row)
+}
+
+/// Subtract a feature row from an accumulator
+#[inline]
+pub fn sub(acc: &mut [i16], row: &[i16]) {
+    #[cfg(target_arch = "x86_64")]
+    if is_x86_feature_detected!("avx2") {
+        // SAFETY: AVX2 support was just checked
+        return unsafe { avx2::sub(acc, row) };
+    }
+    scalar::sub(acc, row)
+}
+
+/// Dot product of the accumulator clipped to `0..=qa` with the output weights
+#[inline]
+pub fn crelu_dot(acc: &[i16], weights: &[i16], qa: i16) -> i32 {
+    #[cfg(target_arch = "x86_64")]
+    if is_x86_feature_detected!("avx2") {
+        // SAFETY: AVX2 support was just checked
+        return unsafe { avx2::crelu_dot(acc, weights, qa) };
+    }
+    scalar::crelu_dot(acc, weights, qa)
+}
+
+/// Portable fallback
+pub mod scalar {
+    pub fn add(acc: &mut [i16], row: &[i16]) {
+        for (a, r) in acc.iter_mut().zip(row) {
+            *a = a.wrapping_add(*r);
+        }
+    }
+
+    pub fn sub(acc: &mut [i16], row: &[i16]) {
+        for (a, r) in acc.iter_mut().zip(row) {
+            *a = a.wrapping_sub(*r);
+        }
+    }
+
+    pub fn crelu_dot(acc: &[i16], weights: &[i16], qa: i16) -> i32 {
+        acc.iter()
+            .zip(weights)
+            .map(|(&a, &w)| a.clamp(0, qa) as i32 * w as i32)
+            .fold(0i32, i32::wrapping_add)
+    }
+}
+
+#[cfg(target_arch = "x86_64")]
+mod avx2 {
+    use std::arch::x86_64::*;
+
+    /// # Safety
+    /// The CPU must support AVX2.
+    #[target_feature(enable = "avx2")]
+    pub unsafe fn add(acc: &mut [i16], row: &[i16]) {
+        for (a, r) in acc.chunks_exact_mut(16).zip(row.chunks_exact(16)) {
+            // SAFETY: both chunks hold exactly 16 i16 values (256 bits)
+            unsafe {
+                let va = _mm256_loadu_si256(a.as_ptr() as *const __m256i);
+                let vr = _mm256_loadu_si256(r.as_ptr() as *const __m256i);
+                _mm256_storeu_si256(a.as_mut_ptr() as *mut __m256i, _mm256_add_epi16(va, vr));
+            }
+        }
+    }
+
+    /// # Safety
+    /// The CPU must support AVX2.
+    #[target_feature(enable = "avx2")]
+    pub unsafe fn sub(acc: &mut [i16], row: &[i16]) {
+        for (a, r) in acc.chunks_exact_mut(16).zip(row.chunks_exact(16)) {
+            // SAFETY: both chunks hold exactly 16 i16 values (256 bits)
+            unsafe {
+                let va = _mm256_loadu_si256(a.as_ptr() as *const __m256i);
+                let vr = _mm256_loadu_si256(r.as_ptr() as *const __m256i);
+                _mm256_storeu_si256(a.as_mut_ptr() as *mut __m256i, _mm256_sub_epi16(va, vr));
+            }
+        }
+    }
+
+    /// # Safety
+    /// The CPU must support AVX2.
+    #[target_feature(enable = "avx2")]
+    pub unsafe fn crelu_dot(acc: &[i16], weights: &[i16], qa: i16) -> i32 {
+        let zero = _mm256_setzero_si256();
+        let ceiling = _mm256_set1_epi16(qa);
+        let mut sum = _mm256_setzero_si256();
+        for (a, w) in acc.chunks_exact(16).zip(weights.chunks_exact(16)) {
+            // SAFETY: both chunks hold exactly 16 i16 values (256 bits)
+            let (va, vw) = unsafe {
+                (
+                    _mm256_loadu_si256(a.as_ptr() as *const __m256i),
+                    _mm256_loadu_si256(w.as_ptr() as *const __m256i),
+                )
+            };
+            let clipped = _mm256_min_epi16(_mm256_max_epi16(va, zero), ceiling);
+            sum = _mm256_add_epi32(sum, _mm256_madd_epi16(clipped, vw));
+        }
+        let mut lanes = [0i32; 8];
+        // SAFETY: `lanes` holds exactly 8 i32 values (256 bits)
+        unsafe { _mm256_storeu_si256(lanes.as_mut_ptr() as *mut __m256i, sum) };
+        lanes.iter().fold(0i32, |total, &lane| total.wrapping_add(lane))
+    }
+}
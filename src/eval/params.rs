@@ -0,0 +1,172 @@
+// src/eval/params.rs
+
+//! Evaluation weights.
+//! Every term of the evaluation reads its value from an `EvalParams`, so a tuner can
+//! adjust the weights without touching the evaluation code. The evaluation is generic
+//! over the value type: `Score` when playing, per-weight coefficients when tuning.
+
+use std::fmt::Write;
+
+use super::score::Score;
+use super::weights::DEFAULT_PARAMS;
+
+#[derive(Clone, Debug)]
+pub struct EvalParams<T = Score> {
+    /// Material value by piece type
+    pub material: [T; 6],
+    /// Piece-square tables by piece type, from White's point of view and laid out as
+    /// the board is drawn (a8 first); White looks up `square ^ 56`, Black `square`
+    pub pst: [[T; 64]; 6],
+    /// Mobility by number of safe target squares
+    pub knight_mobility: [T; 9],
+    pub bishop_mobility: [T; 14],
+    pub rook_mobility: [T; 15],
+    pub queen_mobility: [T; 28],
+    /// Per extra pawn on a file
+    pub doubled_pawn: T,
+    /// Per pawn without friendly pawns on the adjacent files
+    pub isolated_pawn: T,
+    /// Passed pawn by rank, from the pawn's own side
+    pub passed_pawn: [T; 8],
+    pub bishop_pair: T,
+    /// Minor piece on a pawn-defended square no enemy pawn can attack
+    pub knight_outpost: T,
+    pub bishop_outpost: T,
+    pub rook_open_file: T,
+    pub rook_semi_open_file: T,
+    /// Own pawns directly in front of the king, one and two ranks ahead
+    pub king_shield: [T; 2],
+    /// Per attacked square next to the enemy king, by attacking piece type
+    pub king_attack: [T; 6],
+    /// Bonus for the side to move
+    pub tempo: T,
+}
+
+impl<T> EvalParams<T> {
+    /// Apply `f` to every weight, in declaration order (tables row by row)
+    pub fn map<U>(&self, mut f: impl FnMut(&T) -> U) -> EvalParams<U> {
+        EvalParams {
+            material: self.material.each_ref().map(&mut f),
+            pst: self.pst.each_ref().map(|table| table.each_ref().map(&mut f)),
+            knight_mobility: self.knight_mobility.each_ref().map(&mut f),
+            bishop_mobility: self.bishop_mobility.each_ref().map(&mut f),
+            rook_mobility: self.rook_mobility.each_ref().map(&mut f),
+            queen_mobility: self.queen_mobility.each_ref().map(&mut f),
+            doubled_pawn: f(&self.doubled_pawn),
+            isolated_pawn: f(&self.isolated_pawn),
+            passed_pawn: self.passed_pawn.each_ref().map(&mut f),
+            bishop_pair: f(&self.bishop_pair),
+            knight_outpost: f(&self.knight_outpost),
+            bishop_outpost: f(&self.bishop_outpost),
+            rook_open_file: f(&self.rook_open_file),
+            rook_semi_open_file: f(&self.rook_semi_open_file),
+            king_shield: self.king_shield.each_ref().map(&mut f),
+            king_attack: self.king_attack.each_ref().map(&mut f),
+            tempo: f(&self.tempo),
+        }
+    }
+
+    /// All weights in the order of `map`
+    pub fn to_vec(&self) -> Vec<T>
+    where
+        T: Clone,
+    {
+        let mut values = Vec::new();
+        self.map(|value| values.push(value.clone()));
+        values
+    }
+
+    /// Weights taken in the order of `map` from `values`, which must hold at least
+    /// as many as `to_vec` returns
+    pub fn from_values<U>(&self, values: &[U]) -> EvalParams<U>
+    where
+        U: Clone,
+    {
+        let mut next = values.iter();
+        self.map(|_| next.next().expect("too few weights").clone())
+    }
+}
+
+impl EvalParams {
+    /// Rust source of `weights.rs` holding these weights as `DEFAULT_PARAMS`
+    pub fn to_rust(&self) -> String {
+        let mut out = String::from(WEIGHTS_HEADER);
+        field(&mut out, "material", &self.material);
+        out.push_str("    pst: [\n");
+        for (name, table) in PIECE_NAMES.iter().zip(&self.pst) {
+            let _ = writeln!(out, "        // {}", name);
+            out.push_str("        [\n");
+            for rank in table.chunks(8) {
+                let _ = writeln!(out, "            {},", join(rank));
+            }
+            out.push_str("        ],\n");
+        }
+        out.push_str("    ],\n");
+        field(&mut out, "knight_mobility", &self.knight_mobility);
+        field(&mut out, "bishop_mobility", &self.bishop_mobility);
+        field(&mut out, "rook_mobility", &self.rook_mobility);
+        field(&mut out, "queen_mobility", &self.queen_mobility);
+        field(&mut out, "doubled_pawn", &[self.doubled_pawn]);
+        field(&mut out, "isolated_pawn", &[self.isolated_pawn]);
+        field(&mut out, "passed_pawn", &self.passed_pawn);
+        field(&mut out, "bishop_pair", &[self.bishop_pair]);
+        field(&mut out, "knight_outpost", &[self.knight_outpost]);
+        field(&mut out, "bishop_outpost", &[self.bishop_outpost]);
+        field(&mut out, "rook_open_file", &[self.rook_open_file]);
+        field(&mut out, "rook_semi_open_file", &[self.rook_semi_open_file]);
+        field(&mut out, "king_shield", &self.king_shield);
+        field(&mut out, "king_attack", &self.king_attack);
+        field(&mut out, "tempo", &[self.tempo]);
+        out.push_str("};\n");
+        out
+    }
+}
+
+impl Default for EvalParams {
+    fn default() -> Self {
+        DEFAULT_PARAMS.clone()
+    }
+}
+
+const PIECE_NAMES: [&str; 6] = ["Pawn", "Knight", "Bishop", "Rook", "Queen", "King"];
+
+const WEIGHTS_HEADER: &str = "\
+// src/eval/weights.rs
+
+//! Default evaluation weights; the piece-square tables started out as PeSTO's.
+//! Written by the `tune` binary through `EvalParams::to_rust`: regenerate it rather
+//! than reformatting it by hand.
+
+use super::params::EvalParams;
+use super::score::Score;
+
+/// Shorthand for the tables below
+const fn s(mg: i32, eg: i32) -> Score {
+    Score::new(mg, eg)
+}
+
+/// Weights used by `evaluate`
+#[rustfmt::skip]
+pub static DEFAULT_PARAMS: EvalParams = EvalParams {
+";
+
+/// One field of the static: a single value, a short array on one line, or a longer
+/// array eight values per line
+fn field(out: &mut String, name: &str, values: &[Score]) {
+    if let [value] = values {
+        let _ = writeln!(out, "    {}: {},", name, join(&[*value]));
+    } else if values.len() <= 6 {
+        let _ = writeln!(out, "    {}: [{}],", name, join(values));
+    } else {
+        let _ = writeln!(out, "    {}: [", name);
+        for line in values.chunks(8) {
+            let _ = writeln!(out, "        {},", join(line));
+        }
+        out.push_str("    ],\n");
+    }
+}
+
+fn join(values: &[Score]) -> String {
+    let cells: Vec<String> = values.iter().map(|v| format!("s({}, {})", v.mg, v.eg)).collect();
+    cells.join(", ")
+}
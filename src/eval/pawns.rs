@@ -0,0 +1,220 @@
+// src/eval/pawns.rs
+
+//! Pawn structure evaluation and its cache.
+//! Everything in a `PawnEntry` depends only on the two pawn bitboards (the king shield
+//! also on the king square), so entries are kept in a small table keyed by
+//! `Position::pawn_hash` and pawn structures seen before cost a single lookup.
+
+use super::params::EvalParams;
+use super::score::{Score, Weight};
+use crate::board::bitboard::{FILE_A, FILE_H};
+use crate::board::{pieces, sides, BitBoard, Position};
+
+/// Entries in a `PawnTable`
+pub const PAWN_TABLE_SIZE: usize = 1 << 14;
+
+/// Shield king square marking a shield that has not been computed yet
+const NO_SQUARE: usize = 64;
+
+#[derive(Clone, Debug)]
+pub struct PawnEntry<T = Score> {
+    /// Pawn key this entry was computed for
+    key: u64,
+    /// Doubled, isolated and passed pawn terms, White minus Black
+    pub score: T,
+    /// Passed pawns by side
+    pub passed: [BitBoard; 2],
+    /// Squares attacked by the pawns of each side
+    pub attacks: [BitBoard; 2],
+    /// Squares the pawns of each side can no longer attack, however they advance
+    pub weak: [BitBoard; 2],
+    /// Files holding no pawn of each side, as full-file masks
+    pub open_files: [BitBoard; 2],
+    /// King square each side's shield was computed for
+    shield_king: [usize; 2],
+    shield: [T; 2],
+}
+
+impl<T: Weight> PawnEntry<T> {
+    /// Evaluate the pawn structure of a position from scratch
+    pub fn new(pos: &Position, params: &EvalParams<T>) -> Self {
+        let mut entry = PawnEntry {
+            key: pos.pawn_hash,
+            score: T::zero(),
+            passed: [BitBoard::empty(); 2],
+            attacks: [BitBoard::empty(); 2],
+            weak: [BitBoard::empty(); 2],
+            open_files: [BitBoard::empty(); 2],
+            shield_king: [NO_SQUARE; 2],
+            shield: [T::zero(), T::zero()],
+        };
+        for side in [sides::WHITE, sides::BLACK] {
+            let ours = pos.bb_pieces[side][pieces::PAWN];
+            let theirs = pos.bb_pieces[side ^ 1][pieces::PAWN];
+            let mut score = T::zero();
+
+            for file in 0..8 {
+                let count = (ours & file_mask(file)).popcount() as i32;
+                if count > 1 {
+                    score += params.doubled_pawn.clone() * (count - 1);
+                }
+                if count == 0 {
+                    entry.open_files[side] |= file_mask(file);
+                }
+            }
+
+            for square in ours.iter() {
+                let file = square % 8;
+                if (ours & adjacent_files(file)).is_empty() {
+                    score += params.isolated_pawn.clone();
+                }
+                let span = forward_ranks(side, square) & (file_mask(file) | adjacent_files(file));
+                if (theirs & span).is_empty() {
+                    entry.passed[side].set_bit(square);
+                    score += params.passed_pawn[relative_rank(side, square)].clone();
+                }
+            }
+
+            entry.attacks[side] = pawn_attacks_all(side, ours);
+            entry.weak[side] = !forward_fill(side, entry.attacks[side]);
+            if side == sides::WHITE {
+                entry.score += score;
+            } else {
+                entry.score -= score;
+            }
+        }
+        entry
+    }
+
+    /// Score of the pawns sheltering `side`'s king, recomputed only when the king has
+    /// moved since the last call
+    pub fn king_shield(&mut self, pos: &Position, params: &EvalParams<T>, side: usize) -> T {
+        let king = pos.king_square(side);
+        if self.shield_king[side] != king {
+            self.shield_king[side] = king;
+            self.shield[side] = king_shield(pos, params, side, king);
+        }
+        self.shield[side].clone()
+    }
+}
+
+/// Cache of pawn structure evaluations, owned by one search thread
+pub struct PawnTable {
+    entries: Vec<Option<PawnEntry>>,
+}
+
+impl PawnTable {
+    pub fn new() -> Self {
+        PawnTable {
+            entries: vec![None; PAWN_TABLE_SIZE],
+        }
+    }
+
+    /// Entry for the pawn structure of `pos`, computed and stored on a miss.
+    /// Entries are only valid for the `params` they were computed with.
+    pub fn probe(&mut self, pos: &Position, params: &EvalParams) -> &mut PawnEntry {
+        let slot = &mut self.entries[(pos.pawn_hash as usize) & (PAWN_TABLE_SIZE - 1)];
+        match slot {
+            Some(entry) if entry.key == pos.pawn_hash => {}
+            _ => *slot = Some(PawnEntry::new(pos, params)),
+        }
+        slot.as_mut().unwrap()
+    }
+
+    /// Forget all entries
+    pub fn clear(&mut self) {
+        self.entries.fill(None);
+    }
+}
+
+impl Default for PawnTable {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Own pawns one and two ranks in front of the king and the files next to it
+fn king_shield<T: Weight>(
+    pos: &Position,
+    params: &EvalParams<T>,
+    side: usize,
+    king: usize,
+) -> T {
+    let ours = pos.bb_pieces[side][pieces::PAWN];
+    let files = file_mask(king % 8) | adjacent_files(king % 8);
+    let rank = king / 8;
+
+    let mut score = T::zero();
+    for (distance, weight) in params.king_shield.iter().enumerate() {
+        let shield_rank = if side == sides::WHITE {
+            rank + distance + 1
+        } else {
+            rank.wrapping_sub(distance + 1)
+        };
+        if shield_rank < 8 {
+            let shield = files & BitBoard(0xFF << (8 * shield_rank));
+            let count = (ours & shield).popcount() as i32;
+            if count > 0 {
+                score += weight.clone() * count;
+            }
+        }
+    }
+    score
+}
+
+/// All squares of `file`
+fn file_mask(file: usize) -> BitBoard {
+    BitBoard(FILE_A.0 << file)
+}
+
+/// The files next to `file`
+fn adjacent_files(file: usize) -> BitBoard {
+    let mut mask = BitBoard::empty();
+    if file > 0 {
+        mask |= file_mask(file - 1);
+    }
+    if file < 7 {
+        mask |= file_mask(file + 1);
+    }
+    mask
+}
+
+/// Ranks strictly in front of `square` from `side`'s point of view
+fn forward_ranks(side: usize, square: usize) -> BitBoard {
+    let rank = square / 8;
+    if side == sides::WHITE {
+        BitBoard(u64::MAX.checked_shl(8 * (rank as u32 + 1)).unwrap_or(0))
+    } else {
+        BitBoard((1u64 << (8 * rank)) - 1)
+    }
+}
+
+/// Rank of `square` counted from `side`'s back rank
+pub fn relative_rank(side: usize, square: usize) -> usize {
+    if side == sides::WHITE { square / 8 } else { 7 - square / 8 }
+}
+
+/// Squares attacked by any pawn of `side`
+fn pawn_attacks_all(side: usize, pawns: BitBoard) -> BitBoard {
+    let (west, east) = ((pawns & !FILE_A).0, (pawns & !FILE_H).0);
+    if side == sides::WHITE {
+        BitBoard((west << 7) | (east << 9))
+    } else {
+        BitBoard((west >> 9) | (east >> 7))
+    }
+}
+
+/// Smear a set of squares towards the far side of the board from `side`'s point of view
+fn forward_fill(side: usize, bb: BitBoard) -> BitBoard {
+    let mut bits = bb.0;
+    if side == sides::WHITE {
+        bits |= bits << 8;
+        bits |= bits << 16;
+        bits |= bits << 32;
+    } else {
+        bits |= bits >> 8;
+        bits |= bits >> 16;
+        bits |= bits >> 32;
+    }
+    BitBoard(bits)
+}
@@ -0,0 +1,9 @@
+// src/lib.rs
+
+pub mod board;
+pub mod book;
+pub mod eval;
+pub mod search;
+pub mod syzygy;
+pub mod tune;
+pub mod uci;
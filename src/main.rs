@@ -1 +1,6 @@
-wd
+// src/main.rs
+
+fn main() {
+    chessr::board::magic::init();
+    chessr::uci::run();
+}
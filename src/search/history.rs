@@ -0,0 +1,128 @@
+// src/search/history.rs
+
+//! Move ordering statistics gathered by one search thread.
+//! Killers are quiet moves that caused a cutoff at the same ply, the countermove is
+//! the quiet move that last refuted the opponent's previous move, and the history
+//! tables score quiet moves by how often they caused cutoffs: by side and squares
+//! (butterfly) and by the moved piece and target square of the one or two moves
+//! before (continuation).
+
+use super::MAX_PLY;
+use crate::board::{Move, Position};
+
+/// Bound of every history score; updates pull scores back towards zero as they
+/// approach it, so old statistics fade
+pub const MAX_HISTORY: i32 = 16_384;
+
+/// Largest history change from a single cutoff
+const MAX_BONUS: i32 = 1_600;
+
+/// Moved piece (`side * 6 + piece`) and target square of a move already made
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct PieceTo {
+    pub piece: usize,
+    pub to: usize,
+}
+
+impl PieceTo {
+    /// Key of `mv` in `pos`, before the move is made
+    pub fn of(pos: &Position, mv: Move) -> Option<PieceTo> {
+        let (side, piece) = pos.piece_at(mv.from())?;
+        Some(PieceTo { piece: side * 6 + piece, to: mv.to() })
+    }
+
+    fn index(self) -> usize {
+        self.piece * 64 + self.to
+    }
+}
+
+pub struct History {
+    /// Two quiet moves that caused cutoffs, by ply; most recent first
+    pub killers: [[Move; 2]; MAX_PLY],
+    /// Quiet reply that refuted a move, by the refuted move's `PieceTo`
+    countermoves: Box<[Move]>,
+    /// Score of quiet moves by side, from and to square
+    butterfly: Box<[[[i32; 64]; 64]; 2]>,
+    /// Score of a quiet move's `PieceTo` following an earlier move's `PieceTo`
+    continuation: Box<[i32]>,
+}
+
+impl History {
+    pub fn new() -> Self {
+        History {
+            killers: [[Move::NONE; 2]; MAX_PLY],
+            countermoves: vec![Move::NONE; 12 * 64].into_boxed_slice(),
+            butterfly: Box::new([[[0; 64]; 64]; 2]),
+            continuation: vec![0; 12 * 64 * 12 * 64].into_boxed_slice(),
+        }
+    }
+
+    /// Ordering score of a quiet move of `side`, after the moves keyed by `previous`
+    /// (one and two plies back)
+    pub fn quiet_score(
+        &self,
+        side: usize,
+        mv: Move,
+        key: PieceTo,
+        previous: &[Option<PieceTo>],
+    ) -> i32 {
+        let mut score = self.butterfly[side][mv.from()][mv.to()];
+        for earlier in previous.iter().flatten() {
+            score += self.continuation[earlier.index() * 12 * 64 + key.index()];
+        }
+        score
+    }
+
+    /// Butterfly score of a quiet move
+    pub fn butterfly(&self, side: usize, mv: Move) -> i32 {
+        self.butterfly[side][mv.from()][mv.to()]
+    }
+
+    /// Quiet reply that last refuted the move keyed by `previous`
+    pub fn countermove(&self, previous: Option<PieceTo>) -> Move {
+        previous.map_or(Move::NONE, |prev| self.countermoves[prev.index()])
+    }
+
+    /// Reward the quiet move `best` for a cutoff at `depth` and punish the quiet moves
+    /// searched before it without one
+    pub fn update_quiets(
+        &mut self,
+        pos: &Position,
+        ply: usize,
+        depth: i32,
+        best: Move,
+        tried: &[Move],
+        previous: &[Option<PieceTo>],
+    ) {
+        let killers = &mut self.killers[ply];
+        if killers[0] != best {
+            killers[1] = killers[0];
+            killers[0] = best;
+        }
+        if let Some(prev) = previous.first().copied().flatten() {
+            self.countermoves[prev.index()] = best;
+        }
+
+        let bonus = (depth * depth).min(MAX_BONUS);
+        let side = pos.side_to_move;
+        for &mv in tried {
+            let change = if mv == best { bonus } else { -bonus };
+            gravity(&mut self.butterfly[side][mv.from()][mv.to()], change);
+            let Some(key) = PieceTo::of(pos, mv) else { continue };
+            for earlier in previous.iter().flatten() {
+                gravity(&mut self.continuation[earlier.index() * 12 * 64 + key.index()], change);
+            }
+        }
+    }
+}
+
+impl Default for History {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Add `change` to a history score, scaled down as the score nears `MAX_HISTORY`
+fn gravity(entry: &mut i32, change: i32) {
+    *entry += change - *entry * change.abs() / MAX_HISTORY;
+}
@@ -0,0 +1,38 @@
+// src/search/limits.rs
+
+//! Limits for a single search, as given by the UCI `go` command, and the settings it runs with
+
+use super::params::SearchParams;
+use crate::board::Move;
+
+#[derive(Clone, Debug, Default)]
+pub struct SearchLimits {
+    /// White's remaining time in milliseconds
+    pub wtime: Option<u64>,
+    /// Black's remaining time in milliseconds
+    pub btime: Option<u64>,
+    /// White's increment per move in milliseconds
+    pub winc: Option<u64>,
+    /// Black's increment per move in milliseconds
+    pub binc: Option<u64>,
+    /// Moves until the next time control
+    pub movestogo: Option<u32>,
+    /// Maximum depth in plies
+    pub depth: Option<u32>,
+    /// Maximum number of nodes
+    pub nodes: Option<u64>,
+    /// Search for a mate in this many moves
+    pub mate: Option<u32>,
+    /// Exact time to search in milliseconds
+    pub movetime: Option<u64>,
+    /// Search until `stop`
+    pub infinite: bool,
+    /// Search in ponder mode until `ponderhit` or `stop`
+    pub ponder: bool,
+    /// Restrict the root to these moves (all legal moves if empty)
+    pub searchmoves: Vec<Move>,
+    /// Milliseconds kept back on every move for GUI and network lag (`MoveOverhead`)
+    pub move_overhead: u64,
+    /// Pruning and reduction switches
+    pub params: SearchParams,
+}
@@ -0,0 +1,126 @@
+// src/search/mod.rs
+
+pub mod history;
+pub mod limits;
+pub mod params;
+pub mod picker;
+pub mod searcher;
+pub mod time;
+pub mod tt;
+
+use std::io::Write;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use crate::board::{Move, Position};
+use crate::eval::PawnTable;
+use crate::syzygy::Tablebases;
+
+pub use history::History;
+pub use limits::SearchLimits;
+pub use params::SearchParams;
+pub use picker::MovePicker;
+pub use searcher::Searcher;
+pub use time::TimeManager;
+pub use tt::TranspositionTable;
+
+/// Score bound larger than any reachable score
+pub const INFINITY: i32 = 32_000;
+
+/// Score of being checkmated at the root; mate in n plies scores `MATE - n`
+pub const MATE: i32 = 31_000;
+
+/// Maximum search depth in plies
+pub const MAX_PLY: usize = 128;
+
+/// Score of a tablebase win at the root, below all mate scores; a win found `n` plies
+/// into the search scores `TB_WIN - n`
+pub const TB_WIN: i32 = MATE - 2 * MAX_PLY as i32;
+
+/// Where `info` lines go; the UCI front end writes its own replies to the same place
+pub type Output = Arc<Mutex<dyn Write + Send>>;
+
+/// Flags shared between the UCI thread and a running search
+#[derive(Default)]
+pub struct SearchSignals {
+    /// Set to make the search return as soon as possible
+    pub stop: AtomicBool,
+    /// Set while pondering; cleared by `ponderhit`
+    pub ponder: AtomicBool,
+    /// Output for the `info` lines of the main thread; nothing is reported without one
+    pub output: Option<Output>,
+}
+
+impl SearchSignals {
+    /// Check if the search has been told to stop
+    pub fn stopped(&self) -> bool {
+        self.stop.load(Ordering::Relaxed)
+    }
+
+    /// Check if the search is still pondering
+    pub fn pondering(&self) -> bool {
+        self.ponder.load(Ordering::Relaxed)
+    }
+}
+
+/// Result of a search: best move and the expected reply to ponder on
+#[derive(Copy, Clone, Debug, Default)]
+pub struct SearchResult {
+    pub best_move: Move,
+    pub ponder_move: Option<Move>,
+}
+
+/// Search the position within the given limits and return the best move.
+/// One searcher per pawn table in `pawns` runs on the root (Lazy SMP); the main thread's
+/// result is played. Like `tt`, the tables are kept by the caller between searches.
+/// In `infinite` and `ponder` mode the result is held back until `stop` (or `ponderhit`).
+/// With `tablebases`, a root covered by the tables only searches the moves that keep its
+/// result, and positions inside the search are probed while that can still help.
+pub fn think(
+    pos: &Position,
+    limits: &SearchLimits,
+    signals: &SearchSignals,
+    tt: &TranspositionTable,
+    pawns: &mut [PawnTable],
+    tablebases: Option<&Tablebases>,
+) -> SearchResult {
+    tt.new_search();
+    let mut tablebases = tablebases;
+    let mut root_limits = None;
+    if let Some(tb) = tablebases
+        && let Some(ranking) = tb.rank_root_moves(pos, &limits.searchmoves)
+    {
+        // DTZ-ranked moves already make progress, and a drawn or lost root cannot be
+        // improved on, so probing further down only costs time
+        if ranking.dtz || !ranking.winning {
+            tablebases = None;
+        }
+        root_limits = Some(SearchLimits { searchmoves: ranking.moves, ..limits.clone() });
+    }
+    let limits = root_limits.as_ref().unwrap_or(limits);
+    let (main_pawns, helper_pawns) = pawns.split_first_mut().expect("think: no pawn tables");
+    let node_counts: Vec<AtomicU64> = (0..=helper_pawns.len()).map(|_| AtomicU64::new(0)).collect();
+
+    thread::scope(|scope| {
+        for (id, pawns) in (1..).zip(helper_pawns) {
+            let node_counts = &node_counts;
+            scope.spawn(move || {
+                Searcher::new(id, pos, limits, signals, tt, node_counts, pawns, tablebases)
+                    .iterative_deepening();
+            });
+        }
+
+        let node_counts = &node_counts;
+        let result = Searcher::new(0, pos, limits, signals, tt, node_counts, main_pawns, tablebases)
+            .iterative_deepening();
+
+        while (limits.infinite || signals.pondering()) && !signals.stopped() {
+            thread::sleep(Duration::from_millis(1));
+        }
+        // Helpers run until told to stop
+        signals.stop.store(true, Ordering::Relaxed);
+        result
+    })
+}
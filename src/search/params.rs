@@ -0,0 +1,52 @@
+// src/search/params.rs
+
+/// Switches for the pruning, reduction and extension techniques of the search.
+/// Everything is on by default; turning single techniques off lets matches measure
+/// what each one is worth.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct SearchParams {
+    /// Skip a turn at reduced depth and cut if the opponent still cannot reach beta
+    pub null_move: bool,
+    /// Search late quiet moves at reduced depth, re-searching the ones that raise alpha
+    pub late_move_reductions: bool,
+    /// Cut shallow nodes whose static evaluation is far above beta
+    pub reverse_futility: bool,
+    /// Skip quiet moves at shallow nodes whose static evaluation is far below alpha
+    pub futility: bool,
+    /// Drop shallow nodes far below alpha into quiescence search
+    pub razoring: bool,
+    /// Skip the remaining quiet moves at shallow nodes after the first few
+    pub late_move_pruning: bool,
+    /// Search one ply deeper when in check
+    pub check_extensions: bool,
+}
+
+impl SearchParams {
+    /// Every technique switched on
+    pub const ALL: SearchParams = SearchParams {
+        null_move: true,
+        late_move_reductions: true,
+        reverse_futility: true,
+        futility: true,
+        razoring: true,
+        late_move_pruning: true,
+        check_extensions: true,
+    };
+
+    /// Plain alpha-beta
+    pub const NONE: SearchParams = SearchParams {
+        null_move: false,
+        late_move_reductions: false,
+        reverse_futility: false,
+        futility: false,
+        razoring: false,
+        late_move_pruning: false,
+        check_extensions: false,
+    };
+}
+
+impl Default for SearchParams {
+    fn default() -> Self {
+        Self::ALL
+    }
+}
@@ -0,0 +1,219 @@
+// src/search/picker.rs
+
+//! Staged move picker.
+//! Moves are handed out in the order most likely to cause an early cutoff: the hash
+//! move, captures that do not lose material by MVV-LVA, the two killers, the
+//! countermove, the other quiet moves by history, and last the captures SEE says lose
+//! material. Noisy and quiet moves are generated separately and only once a stage
+//! needs them, so a cutoff early in the list saves generating (and scoring) the rest;
+//! a quiet hash move, killer or countermove is checked on the board instead.
+
+use super::history::{History, PieceTo};
+use crate::board::moves::MAX_MOVES;
+use crate::board::{pieces, Move, MoveList, Position};
+
+/// Material values used for capture ordering, by piece type
+const PIECE_VALUES: [i32; 6] = [100, 320, 330, 500, 900, 0];
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum Stage {
+    HashMove,
+    GoodCaptures,
+    Killers,
+    Countermove,
+    Quiets,
+    BadCaptures,
+    Done,
+}
+
+/// Generated moves with their ordering scores, handed out best first
+struct ScoredMoves {
+    moves: MoveList,
+    scores: [i32; MAX_MOVES],
+    /// Moves before this index have been handed out
+    next: usize,
+}
+
+impl ScoredMoves {
+    fn new(moves: MoveList) -> Self {
+        ScoredMoves { moves, scores: [0; MAX_MOVES], next: 0 }
+    }
+
+    /// Swap the best remaining move to the front of the rest and return it; a full
+    /// sort would mostly be wasted on nodes that cut off after a few moves
+    fn pick_best(&mut self) -> Option<Move> {
+        let len = self.moves.len();
+        if self.next >= len {
+            return None;
+        }
+        let mut best = self.next;
+        for i in self.next + 1..len {
+            if self.scores[i] > self.scores[best] {
+                best = i;
+            }
+        }
+        self.moves.swap(self.next, best);
+        self.scores.swap(self.next, best);
+        self.next += 1;
+        Some(self.moves[self.next - 1])
+    }
+}
+
+pub struct MovePicker {
+    stage: Stage,
+    hash_move: Move,
+    killers: [Move; 2],
+    countermove: Move,
+    /// Moves made one and two plies before, for continuation history
+    previous: [Option<PieceTo>; 2],
+    noisy: Option<ScoredMoves>,
+    quiets: Option<ScoredMoves>,
+    /// Captures held back by the good capture stage
+    bad_captures: MoveList,
+    /// Next killer or bad capture to hand out
+    index: usize,
+}
+
+impl MovePicker {
+    /// Picker for all legal moves. The hash move, killers and countermove are only
+    /// handed out if they are legal here.
+    pub fn new(
+        hash_move: Move,
+        killers: [Move; 2],
+        countermove: Move,
+        previous: [Option<PieceTo>; 2],
+    ) -> Self {
+        MovePicker {
+            stage: Stage::HashMove,
+            hash_move,
+            killers,
+            countermove,
+            previous,
+            noisy: None,
+            quiets: None,
+            bad_captures: MoveList::new(),
+            index: 0,
+        }
+    }
+
+    /// Next move to search, or None when all legal moves have been handed out
+    pub fn next(&mut self, pos: &Position, history: &History) -> Option<Move> {
+        loop {
+            match self.stage {
+                Stage::HashMove => {
+                    self.stage = Stage::GoodCaptures;
+                    let mv = self.hash_move;
+                    if !mv.is_none() && self.is_legal(pos, mv) {
+                        return Some(mv);
+                    }
+                }
+                Stage::GoodCaptures => {
+                    self.noisy(pos);
+                    let noisy = self.noisy.as_mut().unwrap();
+                    while let Some(mv) = noisy.pick_best() {
+                        if mv == self.hash_move {
+                            continue;
+                        }
+                        if pos.see(mv) < 0 {
+                            self.bad_captures.push(mv);
+                            continue;
+                        }
+                        return Some(mv);
+                    }
+                    self.stage = Stage::Killers;
+                }
+                Stage::Killers => {
+                    while self.index < self.killers.len() {
+                        let mv = self.killers[self.index];
+                        self.index += 1;
+                        if !mv.is_none() && mv != self.hash_move && pos.is_legal_quiet(mv) {
+                            return Some(mv);
+                        }
+                    }
+                    self.index = 0;
+                    self.stage = Stage::Countermove;
+                }
+                Stage::Countermove => {
+                    self.stage = Stage::Quiets;
+                    let mv = self.countermove;
+                    if !mv.is_none() && !self.is_special(mv) && pos.is_legal_quiet(mv) {
+                        return Some(mv);
+                    }
+                }
+                Stage::Quiets => {
+                    if self.quiets.is_none() {
+                        self.quiets = Some(ScoredMoves::new(pos.generate_quiet_moves()));
+                    }
+                    let quiets = self.quiets.as_mut().unwrap();
+                    if quiets.next == 0 {
+                        let side = pos.side_to_move;
+                        for (i, &mv) in quiets.moves.iter().enumerate() {
+                            quiets.scores[i] = PieceTo::of(pos, mv).map_or(0, |key| {
+                                history.quiet_score(side, mv, key, &self.previous)
+                            });
+                        }
+                    }
+                    while let Some(mv) = quiets.pick_best() {
+                        let special = mv == self.hash_move
+                            || self.killers.contains(&mv)
+                            || mv == self.countermove;
+                        if !special {
+                            return Some(mv);
+                        }
+                    }
+                    self.stage = Stage::BadCaptures;
+                }
+                Stage::BadCaptures => {
+                    if self.index < self.bad_captures.len() {
+                        self.index += 1;
+                        return Some(self.bad_captures[self.index - 1]);
+                    }
+                    self.stage = Stage::Done;
+                }
+                Stage::Done => return None,
+            }
+        }
+    }
+
+    /// Check if `mv` was handed out before the countermove stage
+    fn is_special(&self, mv: Move) -> bool {
+        mv == self.hash_move || self.killers.contains(&mv)
+    }
+
+    /// Check if `mv` is legal here. Noisy moves are looked up in the list the next stage
+    /// needs anyway; quiet ones are checked on the board, so a cutoff on the hash move
+    /// saves generating the quiets.
+    fn is_legal(&mut self, pos: &Position, mv: Move) -> bool {
+        if mv.is_capture() || mv.is_promotion() {
+            self.noisy(pos).moves.contains(&mv)
+        } else {
+            pos.is_legal_quiet(mv)
+        }
+    }
+
+    /// Noisy moves scored by MVV-LVA, generated on first use
+    fn noisy(&mut self, pos: &Position) -> &mut ScoredMoves {
+        self.noisy.get_or_insert_with(|| {
+            let mut noisy = ScoredMoves::new(pos.generate_noisy_moves());
+            for (i, &mv) in noisy.moves.iter().enumerate() {
+                noisy.scores[i] = mvv_lva(pos, mv);
+            }
+            noisy
+        })
+    }
+}
+
+/// Capture and promotion ordering score: most valuable victim first, then least
+/// valuable attacker; promotions count the value of the new piece
+pub fn mvv_lva(pos: &Position, mv: Move) -> i32 {
+    let mut score = 0;
+    if mv.is_capture() {
+        let victim = pos.piece_at(mv.to()).map_or(pieces::PAWN, |(_, p)| p);
+        let attacker = pos.piece_at(mv.from()).map_or(pieces::PAWN, |(_, p)| p);
+        score += PIECE_VALUES[victim] * 10 - PIECE_VALUES[attacker] / 10;
+    }
+    if let Some(promoted) = mv.promotion() {
+        score += PIECE_VALUES[promoted];
+    }
+    score
+}
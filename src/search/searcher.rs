@@ -0,0 +1,638 @@
+// src/search/searcher.rs
+
+//! Negamax alpha-beta search with iterative deepening and a triangular PV table.
+//! Several searchers can run on the same root (Lazy SMP): each owns its position and
+//! history tables and they cooperate only through the shared transposition table.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::PoisonError;
+
+use super::history::{History, PieceTo};
+use super::limits::SearchLimits;
+use super::picker::{mvv_lva, MovePicker};
+use super::time::TimeManager;
+use super::tt::{Bound, TranspositionTable};
+use super::{SearchResult, SearchSignals, INFINITY, MATE, MAX_PLY, TB_WIN};
+use crate::board::{Move, MoveList, Position};
+use crate::eval::{self, PawnTable, DEFAULT_PARAMS};
+use crate::syzygy::{Tablebases, Wdl};
+
+/// How many nodes pass between checks of the clock and the stop flag
+const CHECK_INTERVAL: u64 = 2048;
+
+/// Reverse futility pruning: deepest node and margin per ply of depth
+const RFP_MAX_DEPTH: i32 = 6;
+const RFP_MARGIN: i32 = 80;
+
+/// Razoring margins by depth, from depth 1
+const RAZOR_MARGIN: [i32; 3] = [300, 450, 600];
+
+/// Futility pruning margins by depth, from depth 1
+const FUTILITY_MARGIN: [i32; 4] = [150, 250, 350, 450];
+
+/// Null move pruning: shallowest node and base depth reduction
+const NULL_MOVE_MIN_DEPTH: i32 = 3;
+const NULL_MOVE_REDUCTION: i32 = 3;
+
+/// Late move pruning: deepest node; `LMP_BASE + depth * depth` moves are searched
+const LMP_MAX_DEPTH: i32 = 4;
+const LMP_BASE: i32 = 3;
+
+/// Late move reductions: shallowest node and moves searched in full before reducing
+const LMR_MIN_DEPTH: i32 = 3;
+const LMR_MIN_MOVES: i32 = 3;
+
+/// Lazy SMP depth skipping: helper `i` skips depth `d` when
+/// `(d + SKIP_PHASE[i]) / SKIP_SIZE[i]` is odd, so helpers spread over different depths
+const SKIP_SIZE: [u32; 20] = [1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 3, 3, 4, 4, 4, 4, 4, 4, 4, 4];
+const SKIP_PHASE: [u32; 20] = [0, 1, 0, 1, 2, 3, 0, 1, 2, 3, 4, 5, 0, 1, 2, 3, 4, 5, 6, 7];
+
+/// State of one search thread
+pub struct Searcher<'a> {
+    /// Thread index; thread 0 is the main thread that reports and enforces limits
+    id: usize,
+    pos: Position,
+    limits: &'a SearchLimits,
+    signals: &'a SearchSignals,
+    tt: &'a TranspositionTable,
+    /// Published node counts of all threads, indexed by thread id
+    node_counts: &'a [AtomicU64],
+    /// Deadlines for this move; only the main thread enforces them
+    time: TimeManager,
+    /// Set while pondering, until the main thread sees `ponderhit`
+    pondering: bool,
+    /// Nodes visited so far by this thread
+    pub nodes: u64,
+    /// Nodes of the other threads as of the last check
+    other_nodes: u64,
+    /// Deepest ply reached in the current iteration
+    seldepth: usize,
+    /// Set once the search has to unwind because of a limit or `stop`
+    stopped: bool,
+    /// Triangular principal variation table: pv[ply][ply..pv_len[ply]]
+    pv: Box<[[Move; MAX_PLY]; MAX_PLY]>,
+    pv_len: [usize; MAX_PLY],
+    /// Killers, countermoves and history scores for move ordering
+    history: History,
+    /// Piece and target square of the move made at each ply; None for a null move
+    moved: [Option<PieceTo>; MAX_PLY],
+    /// Pawn structure cache of the evaluation, kept by the caller between searches
+    pawns: &'a mut PawnTable,
+    /// Tables probed inside the search, if any
+    tablebases: Option<&'a Tablebases>,
+}
+
+impl<'a> Searcher<'a> {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        id: usize,
+        pos: &Position,
+        limits: &'a SearchLimits,
+        signals: &'a SearchSignals,
+        tt: &'a TranspositionTable,
+        node_counts: &'a [AtomicU64],
+        pawns: &'a mut PawnTable,
+        tablebases: Option<&'a Tablebases>,
+    ) -> Self {
+        Searcher {
+            id,
+            pos: pos.clone(),
+            limits,
+            signals,
+            tt,
+            node_counts,
+            time: TimeManager::new(limits, pos.side_to_move),
+            pondering: signals.pondering(),
+            nodes: 0,
+            other_nodes: 0,
+            seldepth: 0,
+            stopped: false,
+            pv: Box::new([[Move::NONE; MAX_PLY]; MAX_PLY]),
+            pv_len: [0; MAX_PLY],
+            history: History::new(),
+            moved: [None; MAX_PLY],
+            pawns,
+            tablebases,
+        }
+    }
+
+    /// Check if this is the main thread
+    fn is_main(&self) -> bool {
+        self.id == 0
+    }
+
+    /// Nodes searched by all threads
+    fn total_nodes(&self) -> u64 {
+        self.nodes + self.other_nodes
+    }
+
+    /// Iterative deepening: search depth 1, 2, ... until a limit is hit,
+    /// printing an `info` line after every completed iteration
+    pub fn iterative_deepening(&mut self) -> SearchResult {
+        let root_moves = self.root_moves();
+        let mut result = SearchResult {
+            best_move: root_moves.first().copied().unwrap_or(Move::NONE),
+            ponder_move: None,
+        };
+        if root_moves.is_empty() {
+            return result;
+        }
+
+        let max_depth = self.limits.depth.unwrap_or(MAX_PLY as u32 - 1).clamp(1, MAX_PLY as u32 - 1);
+        for depth in 1..=max_depth {
+            if !self.is_main() && depth > 1 {
+                let i = (self.id - 1) % SKIP_SIZE.len();
+                if ((depth + SKIP_PHASE[i]) / SKIP_SIZE[i]) & 1 != 0 {
+                    continue;
+                }
+            }
+            self.seldepth = 0;
+            let Some(score) = self.root_search(&root_moves, depth as i32) else {
+                // Stopped before a single root move was searched to this depth
+                break;
+            };
+
+            // An interrupted iteration is only trusted for its first completed move,
+            // which `root_search` already placed in the PV
+            if self.stopped && depth > 1 {
+                if self.pv_len[0] > 0 && self.pv[0][0] != result.best_move {
+                    result.best_move = self.pv[0][0];
+                    result.ponder_move = None;
+                }
+                break;
+            }
+
+            result.best_move = self.pv[0][0];
+            result.ponder_move = (self.pv_len[0] > 1).then(|| self.pv[0][1]);
+            self.publish_nodes();
+            if self.is_main() {
+                self.report(depth, score);
+            }
+
+            if self.stopped {
+                break;
+            }
+            if self.is_main() {
+                self.time.update(result.best_move, score);
+                // With a single legal move there is nothing to think about
+                let forced = root_moves.len() == 1 && self.time.is_limited();
+                if forced || self.iteration_limit_reached(score) {
+                    break;
+                }
+            }
+        }
+        result
+    }
+
+    /// Legal root moves, restricted to `searchmoves` when given
+    fn root_moves(&self) -> MoveList {
+        let mut moves = self.pos.generate_legal_moves();
+        if !self.limits.searchmoves.is_empty() {
+            let all = moves.clone();
+            moves.clear();
+            for &mv in all.iter().filter(|mv| self.limits.searchmoves.contains(mv)) {
+                moves.push(mv);
+            }
+        }
+        moves
+    }
+
+    /// Search all root moves, keeping the best one in the PV. Returns None if the search
+    /// was stopped before any root move was done.
+    fn root_search(&mut self, root_moves: &MoveList, depth: i32) -> Option<i32> {
+        let mut moves = root_moves.clone();
+        // Search the previous best move first
+        if self.pv_len[0] > 0
+            && let Some(i) = moves.iter().position(|&mv| mv == self.pv[0][0])
+        {
+            moves.swap(0, i);
+            moves[1..].sort_by_key(|&mv| -self.order_score(mv));
+        } else {
+            moves.sort_by_key(|&mv| -self.order_score(mv));
+        }
+
+        let mut alpha = -INFINITY;
+        let beta = INFINITY;
+        let mut searched = 0;
+        self.nodes += 1;
+        for &mv in moves.iter() {
+            self.moved[0] = PieceTo::of(&self.pos, mv);
+            self.pos.make_move(mv);
+            let score = -self.negamax(depth - 1, 1, -beta, -alpha);
+            self.pos.unmake_move();
+            if self.stopped {
+                break;
+            }
+            searched += 1;
+            if score > alpha {
+                alpha = score;
+                self.update_pv(0, mv);
+            }
+        }
+        if !self.stopped {
+            self.tt.store(self.pos.hash, self.pv[0][0], alpha, depth, Bound::Exact, 0);
+        }
+        (searched > 0).then_some(alpha)
+    }
+
+    /// Fail-hard negamax alpha-beta search with the pruning and reductions enabled in
+    /// `SearchParams`
+    fn negamax(&mut self, mut depth: i32, ply: usize, mut alpha: i32, beta: i32) -> i32 {
+        let params = self.limits.params;
+        let in_check = self.pos.in_check();
+        if in_check && params.check_extensions {
+            depth += 1;
+        }
+        if depth <= 0 {
+            return self.quiescence(ply, alpha, beta);
+        }
+        self.pv_len[ply] = ply;
+        if self.check_stop() {
+            return 0;
+        }
+        self.nodes += 1;
+        self.seldepth = self.seldepth.max(ply);
+
+        if self.pos.is_repetition_draw(ply) || self.pos.has_insufficient_material() {
+            return 0;
+        }
+        if ply >= MAX_PLY - 1 {
+            return self.evaluate();
+        }
+
+        let tt_entry = self.tt.probe(self.pos.hash, ply);
+        if let Some(entry) = tt_entry
+            && entry.depth >= depth
+            && let Some(score) = tt_cutoff(entry.score, entry.bound, alpha, beta)
+        {
+            return score;
+        }
+        let tt_move = tt_entry.map_or(Move::NONE, |entry| entry.best_move);
+
+        // Right after a capture or pawn move the fifty-move counter is zero, so the
+        // table result holds exactly
+        if let Some(tb) = self.tablebases
+            && self.pos.halfmove_clock == 0
+            && let Some(wdl) = tb.probe_wdl(&mut self.pos)
+        {
+            let (score, bound) = match wdl {
+                Wdl::Win => (TB_WIN - ply as i32, Bound::Lower),
+                Wdl::Loss => (-TB_WIN + ply as i32, Bound::Upper),
+                // Cursed wins and blessed losses are drawn by the fifty-move rule
+                _ => (0, Bound::Exact),
+            };
+            if let Some(cutoff) = tt_cutoff(score, bound, alpha, beta) {
+                self.tt.store(self.pos.hash, Move::NONE, score, depth, bound, ply);
+                return cutoff;
+            }
+        }
+
+        // A mate on the hundredth halfmove still counts
+        if self.pos.halfmove_clock >= 100 {
+            let mated = in_check && self.pos.generate_legal_moves().is_empty();
+            return if mated { -MATE + ply as i32 } else { 0 };
+        }
+
+        // Shallow node pruning relies on the static evaluation, which means nothing in
+        // check, and would cut short the exact scores wanted on the principal variation
+        let pv_node = beta - alpha > 1;
+        let static_eval = if in_check { -INFINITY } else { self.evaluate() };
+        let prune = !pv_node && !in_check;
+        if prune {
+            if params.reverse_futility
+                && depth <= RFP_MAX_DEPTH
+                && beta.abs() < TB_WIN - MAX_PLY as i32
+                && static_eval - RFP_MARGIN * depth >= beta
+            {
+                return beta;
+            }
+
+            if params.razoring
+                && depth <= RAZOR_MARGIN.len() as i32
+                && static_eval + RAZOR_MARGIN[depth as usize - 1] < alpha
+            {
+                let score = self.quiescence(ply, alpha, beta);
+                if score <= alpha {
+                    return alpha;
+                }
+            }
+
+            // Without pieces the side to move may be in zugzwang, where passing would be
+            // the best move if it were allowed
+            let side = self.pos.side_to_move;
+            if params.null_move
+                && depth >= NULL_MOVE_MIN_DEPTH
+                && static_eval >= beta
+                && !self.after_null_move()
+                && self.pos.has_non_pawn_material(side)
+            {
+                let reduction = NULL_MOVE_REDUCTION + depth / 6;
+                self.moved[ply] = None;
+                self.pos.make_null_move();
+                let score = -self.negamax(depth - 1 - reduction, ply + 1, -beta, -beta + 1);
+                self.pos.unmake_null_move();
+                if self.stopped {
+                    return 0;
+                }
+                if score >= beta {
+                    return beta;
+                }
+            }
+        }
+
+        // Quiet moves are only skipped after the first move, and never when alpha is a
+        // mated score that another quiet move might escape
+        let futile = params.futility
+            && prune
+            && depth <= FUTILITY_MARGIN.len() as i32
+            && static_eval + FUTILITY_MARGIN[depth as usize - 1] <= alpha;
+        let late_move_limit = if params.late_move_pruning && prune && depth <= LMP_MAX_DEPTH {
+            LMP_BASE + depth * depth
+        } else {
+            i32::MAX
+        };
+
+        let previous = [ply.checked_sub(1), ply.checked_sub(2)]
+            .map(|earlier| earlier.and_then(|earlier| self.moved[earlier]));
+        let countermove = self.history.countermove(previous[0]);
+        let mut picker = MovePicker::new(tt_move, self.history.killers[ply], countermove, previous);
+
+        let mut best_move = Move::NONE;
+        let mut legal = 0;
+        let mut searched = 0;
+        let mut quiets_searched = MoveList::new();
+        while let Some(mv) = picker.next(&self.pos, &self.history) {
+            legal += 1;
+            let quiet = !mv.is_capture() && !mv.is_promotion();
+            let skippable = quiet && searched > 0 && alpha > -TB_WIN + MAX_PLY as i32;
+            self.moved[ply] = PieceTo::of(&self.pos, mv);
+            self.pos.make_move(mv);
+            let gives_check = self.pos.in_check();
+            if skippable && !gives_check && (futile || searched >= late_move_limit) {
+                self.pos.unmake_move();
+                continue;
+            }
+            searched += 1;
+            if quiet {
+                quiets_searched.push(mv);
+            }
+
+            let reduction = if params.late_move_reductions
+                && quiet
+                && depth >= LMR_MIN_DEPTH
+                && searched > LMR_MIN_MOVES
+                && !in_check
+                && !gives_check
+            {
+                late_move_reduction(depth, searched, pv_node)
+            } else {
+                0
+            };
+            let mut score = 0;
+            if reduction > 0 {
+                score = -self.negamax(depth - 1 - reduction, ply + 1, -alpha - 1, -alpha);
+            }
+            if reduction == 0 || (score > alpha && !self.stopped) {
+                score = -self.negamax(depth - 1, ply + 1, -beta, -alpha);
+            }
+            self.pos.unmake_move();
+            if self.stopped {
+                return 0;
+            }
+            if score >= beta {
+                if quiet {
+                    self.history.update_quiets(
+                        &self.pos,
+                        ply,
+                        depth,
+                        mv,
+                        &quiets_searched,
+                        &previous,
+                    );
+                }
+                self.tt.store(self.pos.hash, mv, beta, depth, Bound::Lower, ply);
+                return beta;
+            }
+            if score > alpha {
+                alpha = score;
+                best_move = mv;
+                self.update_pv(ply, mv);
+            }
+        }
+        if legal == 0 {
+            return if in_check { -MATE + ply as i32 } else { 0 };
+        }
+
+        let bound = if best_move.is_none() { Bound::Upper } else { Bound::Exact };
+        self.tt.store(self.pos.hash, best_move, alpha, depth, bound, ply);
+        alpha
+    }
+
+    /// Check if the position was reached by a null move; two in a row would only hand
+    /// the move back
+    fn after_null_move(&self) -> bool {
+        self.pos.undo_stack.last().is_some_and(|undo| undo.mv.is_none())
+    }
+
+    /// Quiescence search: resolve captures and promotions until the position is quiet,
+    /// so the static evaluation is never taken in the middle of an exchange.
+    /// In check all evasions are searched, since standing pat is not an option.
+    fn quiescence(&mut self, ply: usize, mut alpha: i32, beta: i32) -> i32 {
+        self.pv_len[ply] = ply;
+        if self.check_stop() {
+            return 0;
+        }
+        self.nodes += 1;
+        self.seldepth = self.seldepth.max(ply);
+
+        if ply >= MAX_PLY - 1 {
+            return self.evaluate();
+        }
+
+        if let Some(entry) = self.tt.probe(self.pos.hash, ply)
+            && let Some(score) = tt_cutoff(entry.score, entry.bound, alpha, beta)
+        {
+            return score;
+        }
+
+        let in_check = self.pos.in_check();
+        let mut moves = if in_check {
+            let evasions = self.pos.generate_legal_moves();
+            if evasions.is_empty() {
+                return -MATE + ply as i32;
+            }
+            evasions
+        } else {
+            let stand_pat = self.evaluate();
+            if stand_pat >= beta {
+                return beta;
+            }
+            alpha = alpha.max(stand_pat);
+            self.pos.generate_noisy_moves()
+        };
+        moves.sort_by_key(|&mv| -self.order_score(mv));
+
+        let mut best_move = Move::NONE;
+        for &mv in moves.iter() {
+            // Captures that lose material cannot raise the score above stand pat
+            if !in_check && !mv.is_promotion() && self.pos.see(mv) < 0 {
+                continue;
+            }
+            self.pos.make_move(mv);
+            let score = -self.quiescence(ply + 1, -beta, -alpha);
+            self.pos.unmake_move();
+            if self.stopped {
+                return 0;
+            }
+            if score >= beta {
+                self.tt.store(self.pos.hash, mv, beta, 0, Bound::Lower, ply);
+                return beta;
+            }
+            if score > alpha {
+                alpha = score;
+                best_move = mv;
+                self.update_pv(ply, mv);
+            }
+        }
+
+        let bound = if best_move.is_none() { Bound::Upper } else { Bound::Exact };
+        self.tt.store(self.pos.hash, best_move, alpha, 0, bound, ply);
+        alpha
+    }
+
+    /// Static evaluation from the side to move's point of view
+    fn evaluate(&mut self) -> i32 {
+        #[cfg(feature = "nnue")]
+        if let Some(score) = self.pos.nnue_evaluate() {
+            // Keep network output clear of the mate range
+            return score.clamp(-TB_WIN + MAX_PLY as i32 + 1, TB_WIN - MAX_PLY as i32 - 1);
+        }
+        eval::evaluate_cached(&self.pos, &DEFAULT_PARAMS, self.pawns)
+    }
+
+    /// Ordering score for the root and quiescence search, which sort all their moves:
+    /// captures and promotions by MVV-LVA, then quiet moves by history
+    fn order_score(&self, mv: Move) -> i32 {
+        if !mv.is_capture() && !mv.is_promotion() {
+            return self.history.butterfly(self.pos.side_to_move, mv);
+        }
+        10_000_000 + mvv_lva(&self.pos, mv)
+    }
+
+    /// Make `mv` followed by the child's PV the PV at `ply`
+    fn update_pv(&mut self, ply: usize, mv: Move) {
+        self.pv[ply][ply] = mv;
+        let child_len = self.pv_len[ply + 1].max(ply + 1);
+        for i in ply + 1..child_len {
+            self.pv[ply][i] = self.pv[ply + 1][i];
+        }
+        self.pv_len[ply] = child_len;
+    }
+
+    /// Check node, time and stop limits every few thousand nodes.
+    /// Helper threads only follow the stop flag, which the main thread raises when done.
+    fn check_stop(&mut self) -> bool {
+        if self.stopped {
+            return true;
+        }
+        if self.is_main()
+            && let Some(max_nodes) = self.limits.nodes
+            && self.total_nodes() >= max_nodes
+        {
+            self.stopped = true;
+        }
+        if self.nodes.is_multiple_of(CHECK_INTERVAL) {
+            self.publish_nodes();
+            if self.signals.stopped() {
+                self.stopped = true;
+            }
+            if self.is_main() && !self.pondering() && self.time.hard_limit_reached() {
+                self.stopped = true;
+            }
+        }
+        self.stopped
+    }
+
+    /// Publish this thread's node count and refresh the count of the others
+    fn publish_nodes(&mut self) {
+        self.node_counts[self.id].store(self.nodes, Ordering::Relaxed);
+        self.other_nodes = self
+            .node_counts
+            .iter()
+            .enumerate()
+            .filter(|&(id, _)| id != self.id)
+            .map(|(_, count)| count.load(Ordering::Relaxed))
+            .sum();
+    }
+
+    /// Check if another iteration should be started after one with this score
+    fn iteration_limit_reached(&mut self, score: i32) -> bool {
+        if let Some(moves) = self.limits.mate
+            && score.abs() >= MATE - MAX_PLY as i32
+            && (MATE - score.abs() + 1) / 2 <= moves as i32
+        {
+            return true;
+        }
+        !self.pondering() && self.time.soft_limit_reached()
+    }
+
+    /// Check if the search is still pondering, starting the clock once `ponderhit` arrives
+    fn pondering(&mut self) -> bool {
+        if self.pondering && !self.signals.pondering() {
+            self.pondering = false;
+            self.time.ponderhit();
+        }
+        self.pondering
+    }
+
+    /// Send an `info` line for a completed iteration
+    fn report(&self, depth: u32, score: i32) {
+        let Some(output) = &self.signals.output else { return };
+        let elapsed = self.time.elapsed();
+        let nodes = self.total_nodes();
+        let nps = (nodes as f64 / elapsed.as_secs_f64().max(0.001)) as u64;
+        let pv: Vec<String> = self.pv[0][..self.pv_len[0]].iter().map(|mv| mv.to_string()).collect();
+        let mut out = output.lock().unwrap_or_else(PoisonError::into_inner);
+        // Nothing sensible is left to do if the GUI has gone away
+        let _ = writeln!(
+            out,
+            "info depth {} seldepth {} score {} nodes {} nps {} hashfull {} time {} pv {}",
+            depth,
+            self.seldepth,
+            format_score(score),
+            nodes,
+            nps,
+            self.tt.hashfull(),
+            elapsed.as_millis(),
+            pv.join(" ")
+        );
+        let _ = out.flush();
+    }
+}
+
+/// Depth reduction for the `searched`-th move of a node, growing with both; PV nodes
+/// are reduced a ply less. Always leaves at least one ply to search.
+fn late_move_reduction(depth: i32, searched: i32, pv_node: bool) -> i32 {
+    let reduction = (0.75 + (depth as f64).ln() * (searched as f64).ln() / 2.25) as i32;
+    (reduction - pv_node as i32).clamp(0, depth - 2)
+}
+
+/// Score to return for a table hit whose bound settles the window, if any
+fn tt_cutoff(score: i32, bound: Bound, alpha: i32, beta: i32) -> Option<i32> {
+    match bound {
+        Bound::Exact => Some(score.clamp(alpha, beta)),
+        Bound::Lower if score >= beta => Some(beta),
+        Bound::Upper if score <= alpha => Some(alpha),
+        _ => None,
+    }
+}
+
+/// UCI score string: `cp <centipawns>` or `mate <moves>` (negative when getting mated)
+pub fn format_score(score: i32) -> String {
+    if score.abs() >= MATE - MAX_PLY as i32 {
+        let moves = (MATE - score.abs() + 1) / 2;
+        format!("mate {}", if score > 0 { moves } else { -moves })
+    } else {
+        format!("cp {}", score)
+    }
+}
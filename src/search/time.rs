@@ -0,0 +1,135 @@
+// src/search/time.rs
+
+//! Time management: turns the clock state sent with `go` into deadlines for one move.
+//! The soft deadline is checked between iterations and stretched while the best move
+//! keeps changing or the score falls; the hard deadline stops an iteration midway.
+
+use std::time::{Duration, Instant};
+
+use super::limits::SearchLimits;
+use super::{MATE, MAX_PLY};
+use crate::board::{sides, Move};
+
+/// Moves assumed to be left until the next time control when `movestogo` is not sent
+const DEFAULT_MOVES_TO_GO: u64 = 30;
+
+/// Largest factor the soft deadline is stretched by
+const MAX_STRETCH: f64 = 3.0;
+
+/// Score drop in centipawns between two iterations that doubles the soft deadline
+const FULL_DROP: i32 = 200;
+
+pub struct TimeManager {
+    start: Instant,
+    /// When the deadlines started running: the search start, or `ponderhit` when pondering
+    clock: Instant,
+    /// Time after which no new iteration is started, before stretching
+    soft: Option<Duration>,
+    /// Time after which the search stops, whatever it is doing
+    hard: Option<Duration>,
+    /// Best move changes between iterations, halved after every iteration
+    instability: f64,
+    /// Score fall of the last iteration in centipawns
+    drop: i32,
+    previous_best: Move,
+    previous_score: Option<i32>,
+}
+
+impl TimeManager {
+    /// Deadlines for `side` to move. Searches without a clock or `movetime`, and
+    /// `infinite` searches, get none.
+    pub fn new(limits: &SearchLimits, side: usize) -> Self {
+        let (soft, hard) = deadlines(limits, side).unzip();
+        let start = Instant::now();
+        TimeManager {
+            start,
+            clock: start,
+            soft,
+            hard,
+            instability: 0.0,
+            drop: 0,
+            previous_best: Move::NONE,
+            previous_score: None,
+        }
+    }
+
+    /// Time since the search started
+    pub fn elapsed(&self) -> Duration {
+        self.start.elapsed()
+    }
+
+    /// Start the deadlines over: the time spent pondering was on the opponent's clock
+    pub fn ponderhit(&mut self) {
+        self.clock = Instant::now();
+    }
+
+    /// Check if the search runs against the clock
+    pub fn is_limited(&self) -> bool {
+        self.hard.is_some()
+    }
+
+    /// Hard deadline, if any
+    pub fn hard(&self) -> Option<Duration> {
+        self.hard
+    }
+
+    /// Soft deadline after stretching for the iterations seen so far; never past the
+    /// hard deadline
+    pub fn soft(&self) -> Option<Duration> {
+        let stretch = (1.0 + self.instability) * (1.0 + self.drop as f64 / FULL_DROP as f64);
+        let soft = self.soft?.mul_f64(stretch.min(MAX_STRETCH));
+        Some(soft.min(self.hard?))
+    }
+
+    /// Record the result of a completed iteration
+    pub fn update(&mut self, best_move: Move, score: i32) {
+        self.instability /= 2.0;
+        if !self.previous_best.is_none() && best_move != self.previous_best {
+            self.instability += 1.0;
+        }
+        // Mate scores say nothing about a positional slide
+        let mate_range = MATE - MAX_PLY as i32;
+        self.drop = match self.previous_score {
+            Some(previous) if previous.abs() < mate_range && score.abs() < mate_range => {
+                (previous - score).clamp(0, FULL_DROP)
+            }
+            _ => 0,
+        };
+        self.previous_best = best_move;
+        self.previous_score = Some(score);
+    }
+
+    /// Check if no new iteration should be started
+    pub fn soft_limit_reached(&self) -> bool {
+        self.soft().is_some_and(|soft| self.clock.elapsed() >= soft)
+    }
+
+    /// Check if the search has to stop now
+    pub fn hard_limit_reached(&self) -> bool {
+        self.hard.is_some_and(|hard| self.clock.elapsed() >= hard)
+    }
+}
+
+/// Soft and hard deadlines. A fixed `movetime` is used for both; otherwise the soft
+/// deadline is a share of the remaining clock plus most of the increment, and the hard
+/// one a few times that, always leaving a quarter of the clock.
+fn deadlines(limits: &SearchLimits, side: usize) -> Option<(Duration, Duration)> {
+    if limits.infinite {
+        return None;
+    }
+    let overhead = limits.move_overhead;
+    if let Some(movetime) = limits.movetime {
+        let time = Duration::from_millis(movetime.saturating_sub(overhead).max(1));
+        return Some((time, time));
+    }
+    let (time, inc) = if side == sides::WHITE {
+        (limits.wtime?, limits.winc.unwrap_or(0))
+    } else {
+        (limits.btime?, limits.binc.unwrap_or(0))
+    };
+    let time = time.saturating_sub(overhead).max(1);
+    let moves_to_go = limits.movestogo.map_or(DEFAULT_MOVES_TO_GO, |n| n.max(1) as u64);
+    let soft = (time / moves_to_go + inc * 3 / 4).min(time / 2);
+    let hard = (soft * 4).min(time * 3 / 4);
+    Some((Duration::from_millis(soft.max(1)), Duration::from_millis(hard.max(1))))
+}
@@ -0,0 +1,227 @@
+// src/search/tt.rs
+
+//! Transposition table shared by all search threads.
+//! Each entry stores its data word next to `key ^ data`; a reader only accepts an entry
+//! when XOR-ing the two words gives back the probed key, so an entry torn by concurrent
+//! writers is simply treated as a miss (lockless hashing).
+
+use std::sync::atomic::{AtomicU64, AtomicU8, Ordering};
+
+use super::{MAX_PLY, TB_WIN};
+use crate::board::Move;
+
+/// Default table size in megabytes
+pub const DEFAULT_HASH_MB: usize = 16;
+
+/// Largest accepted table size in megabytes
+pub const MAX_HASH_MB: usize = 65536;
+
+/// Entries per bucket; one bucket fills a 64-byte cache line
+const BUCKET_SIZE: usize = 4;
+
+/// How a stored score relates to the true score of the position
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Bound {
+    /// The score is exact (a PV node)
+    Exact,
+    /// The true score is at least this (a beta cutoff)
+    Lower,
+    /// The true score is at most this (no move raised alpha)
+    Upper,
+}
+
+/// Decoded table entry
+#[derive(Copy, Clone, Debug)]
+pub struct TtEntry {
+    pub best_move: Move,
+    /// Score adjusted to the probing ply (mate distances are relative to the root)
+    pub score: i32,
+    pub depth: i32,
+    pub bound: Bound,
+}
+
+#[derive(Default)]
+struct Entry {
+    /// Zobrist key XOR data
+    key: AtomicU64,
+    /// Packed move (16 bits), score (16), depth (8), bound (2) and age (6)
+    data: AtomicU64,
+}
+
+#[derive(Default)]
+#[repr(align(64))]
+struct Bucket {
+    entries: [Entry; BUCKET_SIZE],
+}
+
+pub struct TranspositionTable {
+    buckets: Vec<Bucket>,
+    /// Search generation, bumped by `new_search` so stale entries are replaced first
+    age: AtomicU8,
+}
+
+impl TranspositionTable {
+    /// Create a table of (at most) `mb` megabytes
+    pub fn new(mb: usize) -> Self {
+        let bytes = mb.clamp(1, MAX_HASH_MB) * 1024 * 1024;
+        let len = (bytes / std::mem::size_of::<Bucket>()).max(1);
+        let mut buckets = Vec::with_capacity(len);
+        buckets.resize_with(len, Bucket::default);
+        TranspositionTable {
+            buckets,
+            age: AtomicU8::new(0),
+        }
+    }
+
+    /// Forget all entries (`ucinewgame`)
+    pub fn clear(&self) {
+        for bucket in &self.buckets {
+            for entry in &bucket.entries {
+                entry.key.store(0, Ordering::Relaxed);
+                entry.data.store(0, Ordering::Relaxed);
+            }
+        }
+        self.age.store(0, Ordering::Relaxed);
+    }
+
+    /// Start a new search generation
+    pub fn new_search(&self) {
+        let age = self.age.load(Ordering::Relaxed);
+        self.age.store((age + 1) & 0x3F, Ordering::Relaxed);
+    }
+
+    /// Look up a position, adjusting mate scores to `ply`
+    pub fn probe(&self, key: u64, ply: usize) -> Option<TtEntry> {
+        let bucket = &self.buckets[self.index(key)];
+        for entry in &bucket.entries {
+            let data = entry.data.load(Ordering::Relaxed);
+            if data != 0 && entry.key.load(Ordering::Relaxed) ^ data == key {
+                let mut decoded = unpack(data);
+                decoded.score = score_from_tt(decoded.score, ply);
+                return Some(decoded);
+            }
+        }
+        None
+    }
+
+    /// Store a search result.
+    /// The slot is the entry already holding this key, otherwise the entry whose depth
+    /// is lowest once older generations are penalised.
+    pub fn store(&self, key: u64, best_move: Move, score: i32, depth: i32, bound: Bound, ply: usize) {
+        let age = self.age.load(Ordering::Relaxed);
+        let bucket = &self.buckets[self.index(key)];
+
+        let mut slot = &bucket.entries[0];
+        let mut lowest = i32::MAX;
+        let mut same_key = None;
+        for entry in &bucket.entries {
+            let data = entry.data.load(Ordering::Relaxed);
+            if data == 0 {
+                if lowest > i32::MIN {
+                    slot = entry;
+                    lowest = i32::MIN;
+                }
+                continue;
+            }
+            if entry.key.load(Ordering::Relaxed) ^ data == key {
+                same_key = Some((entry, data));
+                break;
+            }
+            let old = unpack(data);
+            let age_distance = (age.wrapping_sub(unpack_age(data)) & 0x3F) as i32;
+            let priority = old.depth - 8 * age_distance;
+            if priority < lowest {
+                lowest = priority;
+                slot = entry;
+            }
+        }
+
+        let mut best_move = best_move;
+        if let Some((entry, data)) = same_key {
+            let old = unpack(data);
+            // Keep a deeper result from this search unless the new one is exact
+            if bound != Bound::Exact && unpack_age(data) == age && depth + 3 < old.depth {
+                return;
+            }
+            if best_move.is_none() {
+                best_move = old.best_move;
+            }
+            slot = entry;
+        }
+
+        let data = pack(best_move, score_to_tt(score, ply), depth, bound, age);
+        slot.data.store(data, Ordering::Relaxed);
+        slot.key.store(key ^ data, Ordering::Relaxed);
+    }
+
+    /// Permille of sampled entries written during the current search
+    pub fn hashfull(&self) -> usize {
+        let age = self.age.load(Ordering::Relaxed);
+        let sample = self.buckets.len().min(1000 / BUCKET_SIZE);
+        let used = self.buckets[..sample]
+            .iter()
+            .flat_map(|bucket| bucket.entries.iter())
+            .filter(|entry| {
+                let data = entry.data.load(Ordering::Relaxed);
+                data != 0 && unpack_age(data) == age
+            })
+            .count();
+        used * 1000 / (sample * BUCKET_SIZE)
+    }
+
+    /// Bucket index by multiply-shift, using the high bits of the key
+    fn index(&self, key: u64) -> usize {
+        ((key as u128 * self.buckets.len() as u128) >> 64) as usize
+    }
+}
+
+fn pack(best_move: Move, score: i32, depth: i32, bound: Bound, age: u8) -> u64 {
+    let bound_bits = match bound {
+        Bound::Exact => 1u64,
+        Bound::Lower => 2,
+        Bound::Upper => 3,
+    };
+    best_move.0 as u64
+        | ((score as i16 as u16 as u64) << 16)
+        | ((depth.clamp(0, 255) as u64) << 32)
+        | (bound_bits << 40)
+        | (((age & 0x3F) as u64) << 42)
+}
+
+fn unpack(data: u64) -> TtEntry {
+    TtEntry {
+        best_move: Move(data as u16),
+        score: (data >> 16) as u16 as i16 as i32,
+        depth: ((data >> 32) & 0xFF) as i32,
+        bound: match (data >> 40) & 3 {
+            2 => Bound::Lower,
+            3 => Bound::Upper,
+            _ => Bound::Exact,
+        },
+    }
+}
+
+fn unpack_age(data: u64) -> u8 {
+    ((data >> 42) & 0x3F) as u8
+}
+
+/// Mate and tablebase scores are stored relative to the node instead of the root
+fn score_to_tt(score: i32, ply: usize) -> i32 {
+    if score >= TB_WIN - MAX_PLY as i32 {
+        score + ply as i32
+    } else if score <= -TB_WIN + MAX_PLY as i32 {
+        score - ply as i32
+    } else {
+        score
+    }
+}
+
+fn score_from_tt(score: i32, ply: usize) -> i32 {
+    if score >= TB_WIN - MAX_PLY as i32 {
+        score - ply as i32
+    } else if score <= -TB_WIN + MAX_PLY as i32 {
+        score + ply as i32
+    } else {
+        score
+    }
+}
@@ -0,0 +1,144 @@
+// src/syzygy/index.rs
+
+//! Lookup tables used to turn the pieces of a position into a table index.
+//! Pawnless tables use the eightfold symmetry of the board: the leading piece is moved
+//! into the a1-d1-d4 triangle and, when it sits on the a1-h8 diagonal, the next piece
+//! off the diagonal below it. Pawn tables only mirror files, and are split in four by
+//! the file (a to d) of the leading pawn.
+
+use std::sync::OnceLock;
+
+use crate::board::attacks::king_attacks;
+
+/// Largest number of pieces in a table
+pub const MAX_PIECES: usize = 7;
+
+pub struct Indexing {
+    /// Ways to choose k squares out of n: `binomial[k][n]`
+    pub binomial: [[u64; 64]; MAX_PIECES],
+    /// Squares of the a1-d1-d4 triangle to 0..9, the a1-d4 diagonal last
+    pub map_a1d1d4: [usize; 64],
+    /// Squares below the a1-h8 diagonal to 0..27
+    pub map_b1h1h7: [usize; 64],
+    /// Legal placements of two kings, the first in the triangle, to 0..461
+    pub map_kk: [[usize; 64]; 10],
+    /// Squares a2-h7 to 47..0, so the leading pawn (nearest the edge, then lowest rank)
+    /// has the highest value
+    pub map_pawns: [usize; 64],
+    /// Index of the leading pawn group by pawn count and square of the leading pawn
+    pub lead_pawn_idx: [[u64; 64]; 6],
+    /// Number of leading pawn placements by pawn count and file
+    pub lead_pawns_size: [[u64; 4]; 6],
+}
+
+/// Distance of a square above the a1-h8 diagonal (negative below it)
+pub fn off_diagonal(square: usize) -> i32 {
+    (square / 8) as i32 - (square % 8) as i32
+}
+
+/// The shared lookup tables, built on first use
+pub fn indexing() -> &'static Indexing {
+    static INDEXING: OnceLock<Indexing> = OnceLock::new();
+    INDEXING.get_or_init(Indexing::new)
+}
+
+impl Indexing {
+    fn new() -> Self {
+        let mut ix = Indexing {
+            binomial: [[0; 64]; MAX_PIECES],
+            map_a1d1d4: [0; 64],
+            map_b1h1h7: [0; 64],
+            map_kk: [[0; 64]; 10],
+            map_pawns: [0; 64],
+            lead_pawn_idx: [[0; 64]; 6],
+            lead_pawns_size: [[0; 4]; 6],
+        };
+
+        let mut code = 0;
+        for square in 0..64 {
+            if off_diagonal(square) < 0 {
+                ix.map_b1h1h7[square] = code;
+                code += 1;
+            }
+        }
+
+        let mut code = 0;
+        let mut diagonal = Vec::new();
+        for square in 0..=27 {
+            if square % 8 > 3 {
+                continue;
+            }
+            if off_diagonal(square) < 0 {
+                ix.map_a1d1d4[square] = code;
+                code += 1;
+            } else if off_diagonal(square) == 0 {
+                diagonal.push(square);
+            }
+        }
+        for square in diagonal {
+            ix.map_a1d1d4[square] = code;
+            code += 1;
+        }
+
+        // With the first king on the diagonal the second may not be above it; placements
+        // with both kings on the diagonal come last
+        let mut both_on_diagonal = Vec::new();
+        let mut code = 0;
+        for idx in 0..10 {
+            for first in 0..=27 {
+                if first % 8 > 3 || ix.map_a1d1d4[first] != idx || off_diagonal(first) > 0 {
+                    continue;
+                }
+                // b1 is the only square below the diagonal mapped to 0
+                if idx == 0 && first != 1 {
+                    continue;
+                }
+                for second in 0..64 {
+                    if first == second || king_attacks(first).is_set(second) {
+                        continue;
+                    }
+                    if off_diagonal(first) == 0 && off_diagonal(second) > 0 {
+                        continue;
+                    }
+                    if off_diagonal(first) == 0 && off_diagonal(second) == 0 {
+                        both_on_diagonal.push((idx, second));
+                    } else {
+                        ix.map_kk[idx][second] = code;
+                        code += 1;
+                    }
+                }
+            }
+        }
+        for (idx, second) in both_on_diagonal {
+            ix.map_kk[idx][second] = code;
+            code += 1;
+        }
+
+        ix.binomial[0][0] = 1;
+        for n in 1..64 {
+            for k in 0..MAX_PIECES.min(n + 1) {
+                ix.binomial[k][n] = if k > 0 { ix.binomial[k - 1][n - 1] } else { 0 }
+                    + if k < n { ix.binomial[k][n - 1] } else { 0 };
+            }
+        }
+
+        let mut available = 47;
+        for lead_pawns in 1..=5 {
+            for file in 0..4 {
+                let mut idx = 0;
+                for rank in 1..7 {
+                    let square = rank * 8 + file;
+                    if lead_pawns == 1 {
+                        ix.map_pawns[square] = available;
+                        ix.map_pawns[square ^ 7] = available - 1;
+                        available = available.saturating_sub(2);
+                    }
+                    ix.lead_pawn_idx[lead_pawns][square] = idx;
+                    idx += ix.binomial[lead_pawns - 1][ix.map_pawns[square]];
+                }
+                ix.lead_pawns_size[lead_pawns][file] = idx;
+            }
+        }
+        ix
+    }
+}
@@ -0,0 +1,363 @@
+// src/syzygy/mod.rs
+
+//! Syzygy endgame tablebases.
+//! WDL tables give the result of a position with the fifty-move rule taken into
+//! account (cursed wins and blessed losses are drawn under it) and are probed inside
+//! the search; DTZ tables give the distance to the next capture or pawn move and rank
+//! the root moves. Tables hold no castling rights and no en passant captures, which
+//! are resolved by searching captures first. Files are opened on first use.
+
+pub mod index;
+pub mod table;
+
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::ops::Neg;
+use std::path::PathBuf;
+use std::sync::{Arc, OnceLock};
+
+use crate::board::{pieces, sides, Move, Position};
+use table::{position_material_key, Material, Probed, Table, TableKind};
+
+/// Rank of a root move that wins (or loses) for certain, before DTZ refinements
+const MAX_DTZ: i32 = 1 << 18;
+
+/// Result of a position for the side to move
+#[repr(i32)]
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub enum Wdl {
+    Loss = -2,
+    /// Lost, but saved by the fifty-move rule
+    BlessedLoss = -1,
+    Draw = 0,
+    /// Won, but not before the fifty-move rule draws
+    CursedWin = 1,
+    Win = 2,
+}
+
+impl Wdl {
+    fn from_value(value: i32) -> Option<Wdl> {
+        Some(match value {
+            -2 => Wdl::Loss,
+            -1 => Wdl::BlessedLoss,
+            0 => Wdl::Draw,
+            1 => Wdl::CursedWin,
+            2 => Wdl::Win,
+            _ => return None,
+        })
+    }
+}
+
+impl Neg for Wdl {
+    type Output = Wdl;
+
+    fn neg(self) -> Wdl {
+        Wdl::from_value(-(self as i32)).unwrap()
+    }
+}
+
+/// Number of pieces on the board, kings included
+pub fn piece_count(pos: &Position) -> usize {
+    (pos.bb_sides[sides::WHITE].popcount() + pos.bb_sides[sides::BLACK].popcount()) as usize
+}
+
+/// Root moves that keep the best result the tables allow
+#[derive(Clone, Debug)]
+pub struct RootRanking {
+    pub moves: Vec<Move>,
+    /// The moves were ranked with DTZ tables, so any of them makes progress
+    pub dtz: bool,
+    /// The root is won, possibly only cursed: a win the fifty-move rule turns into a draw
+    pub winning: bool,
+}
+
+/// One material combination, such as KRvK, and its two files
+struct Entry {
+    material: Material,
+    wdl_path: PathBuf,
+    dtz_path: Option<PathBuf>,
+    wdl: OnceLock<Option<Table>>,
+    dtz: OnceLock<Option<Table>>,
+}
+
+/// The tables found in the `SyzygyPath` directories
+pub struct Tablebases {
+    /// Entries by material key, both colourings of each table
+    entries: HashMap<u64, Arc<Entry>>,
+    tables: usize,
+    max_pieces: usize,
+}
+
+impl Tablebases {
+    /// Find the tables in a list of directories, separated like `PATH` entries
+    /// (`:` on Unix, `;` on Windows). Unreadable directories are skipped.
+    pub fn open(paths: &str) -> Self {
+        let dirs: Vec<PathBuf> = env::split_paths(paths).collect();
+        let find = |file: &str| dirs.iter().map(|dir| dir.join(file)).find(|path| path.is_file());
+
+        let mut tablebases = Tablebases { entries: HashMap::new(), tables: 0, max_pieces: 0 };
+        for dir in &dirs {
+            let Ok(listing) = fs::read_dir(dir) else { continue };
+            for file in listing.flatten() {
+                let path = file.path();
+                if path.extension().and_then(|ext| ext.to_str()) != Some(TableKind::Wdl.extension()) {
+                    continue;
+                }
+                let Some(name) = path.file_stem().and_then(|stem| stem.to_str()) else {
+                    continue;
+                };
+                let Some(material) = Material::from_name(name) else { continue };
+                if tablebases.entries.contains_key(&material.key) {
+                    continue;
+                }
+                let entry = Arc::new(Entry {
+                    dtz_path: find(&format!("{}.{}", name, TableKind::Dtz.extension())),
+                    wdl_path: path.clone(),
+                    material,
+                    wdl: OnceLock::new(),
+                    dtz: OnceLock::new(),
+                });
+                tablebases.max_pieces = tablebases.max_pieces.max(entry.material.piece_count);
+                tablebases.tables += 1;
+                tablebases.entries.insert(entry.material.key2, entry.clone());
+                tablebases.entries.insert(entry.material.key, entry);
+            }
+        }
+        tablebases
+    }
+
+    /// Number of WDL tables found
+    pub fn len(&self) -> usize {
+        self.tables
+    }
+
+    /// Check if no tables were found
+    pub fn is_empty(&self) -> bool {
+        self.tables == 0
+    }
+
+    /// Most pieces in any table found
+    pub fn max_pieces(&self) -> usize {
+        self.max_pieces
+    }
+
+    /// Check if the tables can be probed for the position at all
+    pub fn covers(&self, pos: &Position) -> bool {
+        pos.castling_rights == 0 && piece_count(pos) <= self.max_pieces
+    }
+
+    /// Result of the position, or None when a table is missing or unreadable.
+    /// `pos` is returned unchanged.
+    pub fn probe_wdl(&self, pos: &mut Position) -> Option<Wdl> {
+        if !self.covers(pos) {
+            return None;
+        }
+        self.search(pos, false).map(|(wdl, _)| wdl)
+    }
+
+    /// Plies to the next capture or pawn move with best play, positive when winning
+    /// and beyond 100 for cursed wins and blessed losses; 0 for draws. Mated scores -1.
+    /// `pos` is returned unchanged.
+    pub fn probe_dtz(&self, pos: &mut Position) -> Option<i32> {
+        if !self.covers(pos) {
+            return None;
+        }
+        self.dtz(pos)
+    }
+
+    /// Rank the root moves (or `searchmoves`, when given) by their table result and keep
+    /// the best ones. DTZ tables are used when all needed ones exist, WDL tables otherwise.
+    pub fn rank_root_moves(&self, pos: &Position, searchmoves: &[Move]) -> Option<RootRanking> {
+        if !self.covers(pos) {
+            return None;
+        }
+        let mut pos = pos.clone();
+        let moves: Vec<Move> = pos
+            .generate_legal_moves()
+            .iter()
+            .copied()
+            .filter(|mv| searchmoves.is_empty() || searchmoves.contains(mv))
+            .collect();
+        if moves.is_empty() {
+            return None;
+        }
+
+        let (ranks, dtz) = match self.dtz_ranks(&mut pos, &moves) {
+            Some(ranks) => (ranks, true),
+            None => (self.wdl_ranks(&mut pos, &moves)?, false),
+        };
+        let best = *ranks.iter().max()?;
+        Some(RootRanking {
+            moves: moves.iter().zip(&ranks).filter(|&(_, &r)| r == best).map(|(&m, _)| m).collect(),
+            dtz,
+            winning: best > 0,
+        })
+    }
+
+    /// Root ranks from DTZ: quickest wins first, then wins the fifty-move rule spoils,
+    /// draws, losses it saves and finally slowest losses
+    fn dtz_ranks(&self, pos: &mut Position, moves: &[Move]) -> Option<Vec<i32>> {
+        let clock = pos.halfmove_clock as i32;
+        let mut ranks = Vec::with_capacity(moves.len());
+        for &mv in moves {
+            pos.make_move(mv);
+            let dtz = if pos.halfmove_clock == 0 {
+                self.search(pos, false).map(|(wdl, _)| dtz_before_zeroing(-wdl))
+            } else if pos.halfmove_clock >= 100 {
+                Some(0)
+            } else {
+                self.dtz(pos).map(|dtz| -dtz - dtz.signum())
+            };
+            let mates = dtz == Some(2) && pos.in_check() && pos.generate_legal_moves().is_empty();
+            pos.unmake_move();
+            let dtz = if mates { 1 } else { dtz? };
+
+            ranks.push(if dtz > 0 {
+                if dtz + clock <= 99 { MAX_DTZ - dtz } else { MAX_DTZ / 2 - (dtz + clock) }
+            } else if dtz < 0 {
+                if -dtz * 2 + clock < 100 { -MAX_DTZ - dtz } else { -MAX_DTZ / 2 + (-dtz + clock) }
+            } else {
+                0
+            });
+        }
+        Some(ranks)
+    }
+
+    /// Root ranks from WDL alone
+    fn wdl_ranks(&self, pos: &mut Position, moves: &[Move]) -> Option<Vec<i32>> {
+        let mut ranks = Vec::with_capacity(moves.len());
+        for &mv in moves {
+            pos.make_move(mv);
+            let wdl = self.search(pos, false);
+            pos.unmake_move();
+            ranks.push(match -wdl?.0 {
+                Wdl::Loss => -MAX_DTZ,
+                Wdl::BlessedLoss => -MAX_DTZ + 101,
+                Wdl::Draw => 0,
+                Wdl::CursedWin => MAX_DTZ - 101,
+                Wdl::Win => MAX_DTZ,
+            });
+        }
+        Some(ranks)
+    }
+
+    /// WDL value of the position, trying captures (and with `zeroing` pawn moves) first
+    /// since the tables say nothing about en passant and a capture may be the only good
+    /// move. The flag is set when the value comes from such a move, in which case the
+    /// stored DTZ value cannot be trusted.
+    fn search(&self, pos: &mut Position, zeroing: bool) -> Option<(Wdl, bool)> {
+        let moves = pos.generate_legal_moves();
+        let mut best = Wdl::Loss;
+        let mut searched = 0;
+        for &mv in moves.iter() {
+            let pawn_move = pos.piece_at(mv.from()).is_some_and(|(_, p)| p == pieces::PAWN);
+            let searched_move = mv.is_capture() || (zeroing && pawn_move);
+            if !searched_move {
+                continue;
+            }
+            searched += 1;
+            pos.make_move(mv);
+            let value = self.search(pos, false);
+            pos.unmake_move();
+            let value = -value?.0;
+            if value > best {
+                best = value;
+                if value == Wdl::Win {
+                    return Some((value, true));
+                }
+            }
+        }
+
+        // With every move searched the table is not needed (and may be wrong, as for
+        // positions where the only moves are en passant captures)
+        let all_searched = searched > 0 && searched == moves.len();
+        let value = match all_searched {
+            true => best,
+            false => match self.probe_table(pos, TableKind::Wdl, Wdl::Draw)? {
+                Probed::Value(value) => Wdl::from_value(value)?,
+                Probed::ChangeStm => return None,
+            },
+        };
+        if best >= value {
+            return Some((best, best > Wdl::Draw || all_searched));
+        }
+        Some((value, false))
+    }
+
+    fn dtz(&self, pos: &mut Position) -> Option<i32> {
+        let (wdl, zeroing_best) = self.search(pos, true)?;
+        if wdl == Wdl::Draw {
+            return Some(0);
+        }
+        if zeroing_best {
+            return Some(dtz_before_zeroing(wdl));
+        }
+        let sign = (wdl as i32).signum();
+        match self.probe_table(pos, TableKind::Dtz, wdl)? {
+            Probed::Value(dtz) if matches!(wdl, Wdl::CursedWin | Wdl::BlessedLoss) => {
+                Some((dtz + 100) * sign)
+            }
+            Probed::Value(dtz) => Some(dtz * sign),
+            Probed::ChangeStm => self.dtz_other_side(pos, wdl),
+        }
+    }
+
+    /// DTZ from the tables of the other side to move: the best of the moves
+    fn dtz_other_side(&self, pos: &mut Position, wdl: Wdl) -> Option<i32> {
+        let mut min_dtz = i32::MAX;
+        for &mv in pos.generate_legal_moves().iter() {
+            let zeroing = mv.is_capture()
+                || pos.piece_at(mv.from()).is_some_and(|(_, p)| p == pieces::PAWN);
+            pos.make_move(mv);
+            // For zeroing moves the distance is the one before the move; the position
+            // after it only tells which way the result goes
+            let dtz = if zeroing {
+                self.search(pos, false).map(|(wdl, _)| -dtz_before_zeroing(wdl))
+            } else {
+                self.dtz(pos).map(|dtz| -dtz)
+            };
+            let mates = dtz == Some(1) && pos.in_check() && pos.generate_legal_moves().is_empty();
+            pos.unmake_move();
+            let mut dtz = dtz?;
+            if mates {
+                min_dtz = 1;
+            }
+            if !zeroing {
+                dtz += dtz.signum();
+            }
+            if dtz < min_dtz && dtz.signum() == (wdl as i32).signum() {
+                min_dtz = dtz;
+            }
+        }
+        // No legal moves: mated
+        Some(if min_dtz == i32::MAX { -1 } else { min_dtz })
+    }
+
+    /// Raw value of the position in its WDL or DTZ table
+    fn probe_table(&self, pos: &Position, kind: TableKind, wdl: Wdl) -> Option<Probed> {
+        // Two bare kings: every position is drawn and there is no table
+        if piece_count(pos) == 2 {
+            return Some(Probed::Value(0));
+        }
+        let entry = self.entries.get(&position_material_key(pos))?;
+        let (cell, path) = match kind {
+            TableKind::Wdl => (&entry.wdl, Some(&entry.wdl_path)),
+            TableKind::Dtz => (&entry.dtz, entry.dtz_path.as_ref()),
+        };
+        cell.get_or_init(|| path.and_then(|path| Table::open(path, kind, &entry.material).ok()))
+            .as_ref()?
+            .probe(pos, wdl)
+    }
+}
+
+/// DTZ of a position whose best move is a capture or pawn move, from its WDL value
+fn dtz_before_zeroing(wdl: Wdl) -> i32 {
+    match wdl {
+        Wdl::Win => 1,
+        Wdl::CursedWin => 101,
+        Wdl::Draw => 0,
+        Wdl::BlessedLoss => -101,
+        Wdl::Loss => -1,
+    }
+}
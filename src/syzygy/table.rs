@@ -0,0 +1,799 @@
+// src/syzygy/table.rs
+
+//! Syzygy table files: header parsing, value decompression and position indexing.
+//!
+//! A file starts with a magic number and a flag byte, then for each file of the leading
+//! pawn (one entry for pawnless tables) and each side to move stored, the order in which
+//! piece groups are encoded. Values are compressed with recursive pairing followed by a
+//! canonical Huffman code, in blocks of fixed size; a sparse index locates the block
+//! holding a given position index. Multi-byte fields are little-endian except for the
+//! compressed blocks, which are read as big-endian bit streams.
+
+use std::fs::File;
+use std::io;
+use std::path::Path;
+
+use super::index::{indexing, off_diagonal, MAX_PIECES};
+use super::Wdl;
+use crate::board::{pieces, sides, BitBoard, Position};
+
+const WDL_MAGIC: [u8; 4] = [0x71, 0xE8, 0x23, 0x5D];
+const DTZ_MAGIC: [u8; 4] = [0xD7, 0x66, 0x0C, 0xA5];
+
+/// Flags of a `PairsData`
+mod flags {
+    /// DTZ only: the side to move stored (set for Black)
+    pub const STM: u8 = 1;
+    /// DTZ only: values go through a per-result map
+    pub const MAPPED: u8 = 2;
+    /// DTZ only: wins are stored in plies rather than moves
+    pub const WIN_PLIES: u8 = 4;
+    /// DTZ only: losses are stored in plies rather than moves
+    pub const LOSS_PLIES: u8 = 8;
+    /// DTZ only: the value map has 16-bit entries
+    pub const WIDE: u8 = 16;
+    /// Every position of the table has the same value
+    pub const SINGLE_VALUE: u8 = 128;
+}
+
+/// Which of the two table types a file holds
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum TableKind {
+    /// Win/draw/loss, `.rtbw`
+    Wdl,
+    /// Distance to zeroing move, `.rtbz`
+    Dtz,
+}
+
+impl TableKind {
+    pub fn extension(self) -> &'static str {
+        match self {
+            TableKind::Wdl => "rtbw",
+            TableKind::Dtz => "rtbz",
+        }
+    }
+}
+
+/// Material of a table, taken from its name (`KRPvKR`: White's pieces before the `v`)
+#[derive(Clone, Debug)]
+pub struct Material {
+    /// Material key with the named sides as they are
+    pub key: u64,
+    /// Material key with the sides swapped; equal to `key` for symmetric tables
+    pub key2: u64,
+    pub piece_count: usize,
+    pub has_pawns: bool,
+    /// Some piece other than a king is alone of its kind
+    pub has_unique_pieces: bool,
+    /// Pawns of the leading colour, then of the other one
+    pub pawn_count: [usize; 2],
+}
+
+impl Material {
+    /// Parse a table name such as `KQvKR`
+    pub fn from_name(name: &str) -> Option<Self> {
+        let (white, black) = name.split_once('v')?;
+        let mut counts = [[0u8; 6]; 2];
+        for (side, text) in [(sides::WHITE, white), (sides::BLACK, black)] {
+            for c in text.chars() {
+                let piece = "PNBRQK".find(c)?;
+                counts[side][piece] += 1;
+            }
+        }
+        let piece_count: usize = counts.iter().flatten().map(|&n| n as usize).sum();
+        if counts[0][pieces::KING] != 1 || counts[1][pieces::KING] != 1 || piece_count > MAX_PIECES
+        {
+            return None;
+        }
+
+        let pawns = [counts[0][pieces::PAWN] as usize, counts[1][pieces::PAWN] as usize];
+        // The leading colour is the one with fewer pawns, but never one without any
+        let white_leads = pawns[1] == 0 || (pawns[0] > 0 && pawns[1] >= pawns[0]);
+        let pawn_count = if white_leads { pawns } else { [pawns[1], pawns[0]] };
+        Some(Material {
+            key: material_key(&counts),
+            key2: material_key(&[counts[1], counts[0]]),
+            piece_count,
+            has_pawns: pawns[0] + pawns[1] > 0,
+            has_unique_pieces: counts
+                .iter()
+                .any(|side| side[pieces::PAWN..pieces::KING].contains(&1)),
+            pawn_count,
+        })
+    }
+}
+
+/// Key identifying the material of a position: four bits per side and piece type
+pub fn material_key(counts: &[[u8; 6]; 2]) -> u64 {
+    let mut key = 0;
+    for (side, side_counts) in counts.iter().enumerate() {
+        for (piece, &count) in side_counts.iter().enumerate() {
+            key |= (count as u64) << (4 * (6 * side + piece));
+        }
+    }
+    key
+}
+
+/// Material key of a position
+pub fn position_material_key(pos: &Position) -> u64 {
+    let mut counts = [[0u8; 6]; 2];
+    for side in [sides::WHITE, sides::BLACK] {
+        for (piece, count) in counts[side].iter_mut().enumerate() {
+            *count = pos.bb_pieces[side][piece].popcount() as u8;
+        }
+    }
+    material_key(&counts)
+}
+
+/// Compression parameters and piece order of one subtable
+#[derive(Clone, Default)]
+struct PairsData {
+    flags: u8,
+    block_size: usize,
+    /// A sparse index entry exists for every `span` values
+    span: u64,
+    num_blocks: usize,
+    max_sym_len: usize,
+    /// Shortest Huffman code, or the value itself for single-value tables
+    min_sym_len: usize,
+    /// File offsets of the lowest symbol of each code length, the pairing tree,
+    /// the block lengths, the sparse index and the compressed data
+    lowest_sym: usize,
+    btree: usize,
+    block_length: usize,
+    block_length_size: usize,
+    sparse_index: usize,
+    sparse_index_size: usize,
+    data: usize,
+    /// Lowest code of each length, left-aligned in 64 bits
+    base64: Vec<u64>,
+    /// Number of values (minus one) each symbol expands to
+    symlen: Vec<u8>,
+    /// Piece codes in encoding order: type 1 (pawn) to 6 (king), plus 8 for Black
+    pieces: [u8; MAX_PIECES],
+    /// Multiplier of each piece group in the position index
+    group_idx: [u64; MAX_PIECES + 1],
+    /// Pieces in each group, zero-terminated
+    group_len: [usize; MAX_PIECES + 1],
+    /// DTZ only: offsets into the value map for each result
+    map_idx: [u16; 4],
+}
+
+/// Result of looking a position up in a single table
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Probed {
+    Value(i32),
+    /// The DTZ table only stores the other side to move
+    ChangeStm,
+}
+
+/// An opened table file
+pub struct Table {
+    kind: TableKind,
+    material: Material,
+    bytes: Mapping,
+    /// Subtables by side to move (one when only one is stored) and leading pawn file
+    pairs: Vec<Vec<PairsData>>,
+    /// DTZ only: offset of the value maps
+    dtz_map: usize,
+}
+
+impl Table {
+    /// Open and parse a table file
+    pub fn open(path: &Path, kind: TableKind, material: &Material) -> io::Result<Self> {
+        let bytes = Mapping::open(path)?;
+        let mut table = Table {
+            kind,
+            material: material.clone(),
+            bytes,
+            pairs: Vec::new(),
+            dtz_map: 0,
+        };
+        table.parse().ok_or_else(|| {
+            io::Error::new(io::ErrorKind::InvalidData, format!("corrupt table {}", path.display()))
+        })?;
+        Ok(table)
+    }
+
+    fn parse(&mut self) -> Option<()> {
+        let bytes = self.bytes.as_slice();
+        let magic = match self.kind {
+            TableKind::Wdl => WDL_MAGIC,
+            TableKind::Dtz => DTZ_MAGIC,
+        };
+        if bytes.get(..4)? != magic {
+            return None;
+        }
+        let m = &self.material;
+        let header = *bytes.get(4)?;
+        if (header & 2 != 0) != m.has_pawns || (header & 1 != 0) != (m.key != m.key2) {
+            return None;
+        }
+
+        let sides = if self.kind == TableKind::Wdl && m.key != m.key2 { 2 } else { 1 };
+        let files = if m.has_pawns { 4 } else { 1 };
+        let both_pawns = m.has_pawns && m.pawn_count[1] > 0;
+        let mut pairs = vec![vec![PairsData::default(); files]; sides];
+        let mut at = 5;
+
+        for file in 0..files {
+            let first = *bytes.get(at)? as usize;
+            let second = if both_pawns { *bytes.get(at + 1)? as usize } else { 0xFF };
+            let order = [
+                [first & 0xF, if both_pawns { second & 0xF } else { 0xF }],
+                [first >> 4, if both_pawns { second >> 4 } else { 0xF }],
+            ];
+            at += 1 + both_pawns as usize;
+            for k in 0..m.piece_count {
+                let byte = *bytes.get(at)?;
+                for (side, side_pairs) in pairs.iter_mut().enumerate() {
+                    side_pairs[file].pieces[k] = if side == 1 { byte >> 4 } else { byte & 0xF };
+                }
+                at += 1;
+            }
+            for (side, side_pairs) in pairs.iter_mut().enumerate() {
+                set_groups(m, &mut side_pairs[file], order[side], file);
+            }
+        }
+        at += at & 1;
+
+        for file in 0..files {
+            for side_pairs in pairs.iter_mut() {
+                at = set_sizes(&mut side_pairs[file], bytes, at)?;
+            }
+        }
+
+        if self.kind == TableKind::Dtz {
+            self.dtz_map = at;
+            for d in pairs[0].iter_mut() {
+                if d.flags & flags::MAPPED == 0 {
+                    continue;
+                }
+                if d.flags & flags::WIDE != 0 {
+                    at += at & 1;
+                    for i in 0..4 {
+                        d.map_idx[i] = ((at - self.dtz_map) / 2 + 1) as u16;
+                        at += 2 * read_u16(bytes, at)? as usize + 2;
+                    }
+                } else {
+                    for i in 0..4 {
+                        d.map_idx[i] = (at - self.dtz_map + 1) as u16;
+                        at += *bytes.get(at)? as usize + 1;
+                    }
+                }
+            }
+            at += at & 1;
+        }
+
+        for file in 0..files {
+            for side_pairs in pairs.iter_mut() {
+                side_pairs[file].sparse_index = at;
+                at += side_pairs[file].sparse_index_size * 6;
+            }
+        }
+        for file in 0..files {
+            for side_pairs in pairs.iter_mut() {
+                side_pairs[file].block_length = at;
+                at += side_pairs[file].block_length_size * 2;
+            }
+        }
+        for file in 0..files {
+            for side_pairs in pairs.iter_mut() {
+                at = (at + 0x3F) & !0x3F;
+                side_pairs[file].data = at;
+                at += side_pairs[file].num_blocks * side_pairs[file].block_size;
+            }
+        }
+        if at > bytes.len() {
+            return None;
+        }
+        self.pairs = pairs;
+        Some(())
+    }
+
+    fn get(&self, stm: usize, file: usize) -> &PairsData {
+        &self.pairs[stm % self.pairs.len()][if self.material.has_pawns { file } else { 0 }]
+    }
+
+    /// Look up a position whose material matches this table. `wdl` is the position's
+    /// WDL value, which DTZ values are decoded with. None if the file is corrupt.
+    pub fn probe(&self, pos: &Position, wdl: Wdl) -> Option<Probed> {
+        let ix = indexing();
+        let m = &self.material;
+
+        // Tables are written with the stronger side as White, and symmetric tables only
+        // with White to move: otherwise swap the colours and flip the board
+        let symmetric_black = m.key == m.key2 && pos.side_to_move == sides::BLACK;
+        let flip = symmetric_black || position_material_key(pos) != m.key;
+        let flip_colour = if flip { 8 } else { 0 };
+        let flip_squares = if flip { 56 } else { 0 };
+        let stm = flip as usize ^ pos.side_to_move;
+
+        let mut squares = [0usize; MAX_PIECES];
+        let mut codes = [0u8; MAX_PIECES];
+        let mut size = 0;
+        let mut lead_pawns = 0;
+        let mut lead_bb = 0u64;
+        let mut tb_file = 0;
+
+        // Pawn tables are split by the file of the leading pawn: the pawn nearest the
+        // edge, and of those the one on the lowest rank
+        if m.has_pawns {
+            let lead_colour = (self.get(0, 0).pieces[0] ^ flip_colour) >> 3;
+            let bb = pos.bb_pieces[lead_colour as usize][pieces::PAWN];
+            lead_bb = bb.0;
+            for square in bb.iter() {
+                squares[size] = square ^ flip_squares;
+                size += 1;
+            }
+            lead_pawns = size;
+            let lead = (0..lead_pawns).max_by_key(|&i| ix.map_pawns[squares[i]])?;
+            squares.swap(0, lead);
+            let file = squares[0] % 8;
+            tb_file = file.min(7 - file);
+        }
+
+        if self.kind == TableKind::Dtz {
+            let stored = (self.get(stm, tb_file).flags & flags::STM) as usize;
+            let symmetric = m.key == m.key2 && !m.has_pawns;
+            if stored != stm && !symmetric {
+                return Some(Probed::ChangeStm);
+            }
+        }
+
+        for square in (pos.occupied() & !BitBoard(lead_bb)).iter() {
+            let (side, piece) = pos.piece_at(square)?;
+            squares[size] = square ^ flip_squares;
+            codes[size] = (piece as u8 + 1 + 8 * side as u8) ^ flip_colour;
+            size += 1;
+            if size > m.piece_count {
+                return None;
+            }
+        }
+        if size != m.piece_count {
+            return None;
+        }
+
+        let d = self.get(stm, tb_file);
+
+        // Put the pieces in the order the table encodes them
+        for i in lead_pawns..size - 1 {
+            for j in i + 1..size {
+                if d.pieces[i] == codes[j] {
+                    codes.swap(i, j);
+                    squares.swap(i, j);
+                    break;
+                }
+            }
+        }
+
+        // Mirror files so the leading piece is on files a to d
+        if squares[0] % 8 > 3 {
+            for square in squares[..size].iter_mut() {
+                *square ^= 7;
+            }
+        }
+
+        let mut idx;
+        if m.has_pawns {
+            idx = ix.lead_pawn_idx[lead_pawns][squares[0]];
+            squares[1..lead_pawns].sort_by_key(|&square| ix.map_pawns[square]);
+            for (i, &square) in squares[1..lead_pawns].iter().enumerate() {
+                idx += ix.binomial[i + 1][ix.map_pawns[square]];
+            }
+        } else {
+            // Mirror ranks so the leading piece is on ranks 1 to 4, then along the
+            // diagonal so the first leading piece off the diagonal is below it
+            if squares[0] / 8 > 3 {
+                for square in squares[..size].iter_mut() {
+                    *square ^= 56;
+                }
+            }
+            for i in 0..d.group_len[0] {
+                let off = off_diagonal(squares[i]);
+                if off == 0 {
+                    continue;
+                }
+                if off > 0 {
+                    for square in squares[i..size].iter_mut() {
+                        *square = ((*square >> 3) | (*square << 3)) & 63;
+                    }
+                }
+                break;
+            }
+
+            idx = if m.has_unique_pieces {
+                // The leading group is three unique pieces, kings included
+                let [s0, s1, s2] = [squares[0], squares[1], squares[2]];
+                let adjust1 = (s1 > s0) as usize;
+                let adjust2 = (s2 > s0) as usize + (s2 > s1) as usize;
+                let (r0, r1, r2) = (s0 / 8, s1 / 8, s2 / 8);
+                (if off_diagonal(s0) != 0 {
+                    (ix.map_a1d1d4[s0] * 63 + (s1 - adjust1)) * 62 + s2 - adjust2
+                } else if off_diagonal(s1) != 0 {
+                    (6 * 63 + r0 * 28 + ix.map_b1h1h7[s1]) * 62 + s2 - adjust2
+                } else if off_diagonal(s2) != 0 {
+                    6 * 63 * 62 + 4 * 28 * 62 + r0 * 7 * 28 + (r1 - adjust1) * 28 + ix.map_b1h1h7[s2]
+                } else {
+                    6 * 63 * 62 + 4 * 28 * 62 + 4 * 7 * 28 + r0 * 7 * 6 + (r1 - adjust1) * 6
+                        + (r2 - adjust2)
+                }) as u64
+            } else {
+                ix.map_kk[ix.map_a1d1d4[squares[0]]][squares[1]] as u64
+            };
+        }
+
+        // The remaining groups, each as a combination of the squares left free by the
+        // groups before it
+        idx *= d.group_idx[0];
+        let mut start = d.group_len[0];
+        let mut remaining_pawns = m.has_pawns && m.pawn_count[1] > 0;
+        let mut next = 1;
+        while d.group_len[next] != 0 {
+            let len = d.group_len[next];
+            squares[start..start + len].sort_unstable();
+            let mut n = 0;
+            for i in 0..len {
+                let square = squares[start + i];
+                let adjust = squares[..start].iter().filter(|&&s| square > s).count();
+                n += ix.binomial[i + 1][square - adjust - 8 * remaining_pawns as usize];
+            }
+            remaining_pawns = false;
+            idx += n * d.group_idx[next];
+            start += len;
+            next += 1;
+        }
+
+        let value = self.decompress(d, idx)?;
+        Some(Probed::Value(match self.kind {
+            TableKind::Wdl => value - 2,
+            TableKind::Dtz => self.map_dtz(tb_file, value, wdl)?,
+        }))
+    }
+
+    /// Turn a stored DTZ value into plies
+    fn map_dtz(&self, file: usize, value: i32, wdl: Wdl) -> Option<i32> {
+        // Index into `map_idx` by WDL value + 2
+        const WDL_MAP: [usize; 5] = [1, 3, 0, 2, 0];
+        let d = self.get(0, file);
+        let bytes = self.bytes.as_slice();
+        let map_idx = d.map_idx[WDL_MAP[(wdl as i32 + 2) as usize]] as usize;
+        let mut value = value;
+        if d.flags & flags::MAPPED != 0 {
+            value = if d.flags & flags::WIDE != 0 {
+                read_u16(bytes, self.dtz_map + 2 * (map_idx + value as usize))? as i32
+            } else {
+                *bytes.get(self.dtz_map + map_idx + value as usize)? as i32
+            };
+        }
+        let in_moves = match wdl {
+            Wdl::Win => d.flags & flags::WIN_PLIES == 0,
+            Wdl::Loss => d.flags & flags::LOSS_PLIES == 0,
+            Wdl::CursedWin | Wdl::BlessedLoss => true,
+            Wdl::Draw => false,
+        };
+        Some(if in_moves { value * 2 } else { value } + 1)
+    }
+
+    /// Value stored for position index `idx`
+    fn decompress(&self, d: &PairsData, idx: u64) -> Option<i32> {
+        if d.flags & flags::SINGLE_VALUE != 0 {
+            return Some(d.min_sym_len as i32);
+        }
+        let bytes = self.bytes.as_slice();
+
+        // Sparse index entry `k` points at the value with index `k * span + span / 2`;
+        // step through the block lengths from there to the block holding `idx`
+        let k = (idx / d.span) as usize;
+        if k >= d.sparse_index_size {
+            return None;
+        }
+        let entry = d.sparse_index + 6 * k;
+        let mut block = read_u32(bytes, entry)? as i64;
+        let mut offset = read_u16(bytes, entry + 4)? as i64;
+        offset += (idx % d.span) as i64 - (d.span / 2) as i64;
+
+        let block_length = |block: i64| -> Option<i64> {
+            if block < 0 || block as usize >= d.block_length_size {
+                return None;
+            }
+            Some(read_u16(bytes, d.block_length + 2 * block as usize)? as i64)
+        };
+        while offset < 0 {
+            block -= 1;
+            offset += block_length(block)? + 1;
+        }
+        while offset > block_length(block)? {
+            offset -= block_length(block)? + 1;
+            block += 1;
+        }
+
+        // Decode Huffman symbols until reaching the one that covers `offset`
+        let mut ptr = d.data + block as usize * d.block_size;
+        let mut buf = read_u64_be(bytes, ptr)?;
+        ptr += 8;
+        let mut buf_size = 64;
+        let mut sym;
+        loop {
+            let mut len = 0;
+            while buf < d.base64[len] {
+                len += 1;
+                if len >= d.base64.len() {
+                    return None;
+                }
+            }
+            sym = ((buf - d.base64[len]) >> (64 - len - d.min_sym_len)) as usize;
+            sym += read_u16(bytes, d.lowest_sym + 2 * len)? as usize;
+            let count = *d.symlen.get(sym)? as i64 + 1;
+            if offset < count {
+                break;
+            }
+            offset -= count;
+            let bits = len + d.min_sym_len;
+            buf = buf.checked_shl(bits as u32).unwrap_or(0);
+            buf_size -= bits as i32;
+            if buf_size <= 32 {
+                buf_size += 32;
+                buf |= (read_u32_be(bytes, ptr)? as u64) << (64 - buf_size);
+                ptr += 4;
+            }
+        }
+
+        // Expand the symbol down the pairing tree to the value at `offset`
+        while d.symlen[sym] != 0 {
+            let (left, right) = pair(bytes, d.btree, sym)?;
+            let count = *d.symlen.get(left)? as i64 + 1;
+            if offset < count {
+                sym = left;
+            } else {
+                offset -= count;
+                sym = right;
+            }
+            if sym >= d.symlen.len() {
+                return None;
+            }
+        }
+        Some(pair(bytes, d.btree, sym)?.0 as i32)
+    }
+}
+
+/// Split the pieces into groups and work out the index multiplier of each group.
+/// `order` gives the position of the leading group and of the remaining pawns
+/// (0xF when there are none) among the groups.
+fn set_groups(m: &Material, d: &mut PairsData, order: [usize; 2], file: usize) {
+    let ix = indexing();
+    let mut n = 0;
+    let mut first_len: i32 = if m.has_pawns {
+        0
+    } else if m.has_unique_pieces {
+        3
+    } else {
+        2
+    };
+    d.group_len[0] = 1;
+    for i in 1..m.piece_count {
+        first_len -= 1;
+        if first_len > 0 || d.pieces[i] == d.pieces[i - 1] {
+            d.group_len[n] += 1;
+        } else {
+            n += 1;
+            d.group_len[n] = 1;
+        }
+    }
+    n += 1;
+    d.group_len[n] = 0;
+
+    let both_pawns = m.has_pawns && m.pawn_count[1] > 0;
+    let mut next = if both_pawns { 2 } else { 1 };
+    let mut free = 64 - d.group_len[0] - if both_pawns { d.group_len[1] } else { 0 };
+    let mut idx = 1u64;
+    let mut k = 0;
+    while next < n || k == order[0] || k == order[1] {
+        if k == order[0] {
+            d.group_idx[0] = idx;
+            idx *= if m.has_pawns {
+                ix.lead_pawns_size[d.group_len[0]][file]
+            } else if m.has_unique_pieces {
+                31332
+            } else {
+                462
+            };
+        } else if k == order[1] {
+            d.group_idx[1] = idx;
+            idx *= ix.binomial[d.group_len[1]][48 - d.group_len[0]];
+        } else {
+            d.group_idx[next] = idx;
+            idx *= ix.binomial[d.group_len[next]][free];
+            free -= d.group_len[next];
+            next += 1;
+        }
+        k += 1;
+    }
+    d.group_idx[n] = idx;
+}
+
+/// Read the compression parameters of a subtable at `at`; returns the offset past them
+fn set_sizes(d: &mut PairsData, bytes: &[u8], mut at: usize) -> Option<usize> {
+    d.flags = *bytes.get(at)?;
+    at += 1;
+    if d.flags & flags::SINGLE_VALUE != 0 {
+        d.min_sym_len = *bytes.get(at)? as usize;
+        return Some(at + 1);
+    }
+
+    // The last group multiplier is the number of positions in the table
+    let groups = d.group_len.iter().position(|&len| len == 0)?;
+    let size = d.group_idx[groups];
+
+    d.block_size = 1usize.checked_shl(*bytes.get(at)? as u32)?;
+    d.span = 1u64.checked_shl(*bytes.get(at + 1)? as u32)?;
+    d.sparse_index_size = size.div_ceil(d.span) as usize;
+    let padding = *bytes.get(at + 2)? as usize;
+    d.num_blocks = read_u32(bytes, at + 3)? as usize;
+    d.block_length_size = d.num_blocks + padding;
+    d.max_sym_len = *bytes.get(at + 7)? as usize;
+    d.min_sym_len = *bytes.get(at + 8)? as usize;
+    at += 9;
+    if d.min_sym_len == 0 || d.max_sym_len < d.min_sym_len || d.max_sym_len > 64 {
+        return None;
+    }
+
+    // Canonical Huffman code: longer codes have lower values. base64[l] is the lowest
+    // code of length min_sym_len + l, left-aligned so codes compare as 64-bit numbers.
+    d.lowest_sym = at;
+    let lengths = d.max_sym_len - d.min_sym_len + 1;
+    d.base64 = vec![0; lengths];
+    for i in (0..lengths - 1).rev() {
+        let lowest = read_u16(bytes, at + 2 * i)? as u64;
+        let next_lowest = read_u16(bytes, at + 2 * (i + 1))? as u64;
+        d.base64[i] = (d.base64[i + 1] + lowest).checked_sub(next_lowest)? / 2;
+    }
+    for (i, base) in d.base64.iter_mut().enumerate() {
+        *base = base.checked_shl((64 - i - d.min_sym_len) as u32).unwrap_or(0);
+    }
+    at += 2 * lengths;
+
+    let symbols = read_u16(bytes, at)? as usize;
+    at += 2;
+    d.btree = at;
+    bytes.get(at + 3 * symbols - 1)?;
+    d.symlen = vec![0; symbols];
+    let mut visited = vec![false; symbols];
+    for sym in 0..symbols {
+        if !visited[sym] {
+            d.symlen[sym] = symbol_length(bytes, d, &mut visited, sym)?;
+        }
+    }
+    Some(at + 3 * symbols + (symbols & 1))
+}
+
+/// Number of values (minus one) a symbol expands to, filling in its children
+fn symbol_length(bytes: &[u8], d: &mut PairsData, visited: &mut [bool], sym: usize) -> Option<u8> {
+    visited[sym] = true;
+    let (left, right) = pair(bytes, d.btree, sym)?;
+    if right == 0xFFF {
+        return Some(0);
+    }
+    for child in [left, right] {
+        if child >= visited.len() {
+            return None;
+        }
+        if !visited[child] {
+            d.symlen[child] = symbol_length(bytes, d, visited, child)?;
+        }
+    }
+    Some(d.symlen[left].wrapping_add(d.symlen[right]).wrapping_add(1))
+}
+
+/// The two symbols a symbol stands for, packed in 12 bits each; for a leaf (right
+/// 0xFFF) the left one is the stored value
+fn pair(bytes: &[u8], btree: usize, sym: usize) -> Option<(usize, usize)> {
+    let lr = bytes.get(btree + 3 * sym..btree + 3 * sym + 3)?;
+    let left = ((lr[1] as usize & 0xF) << 8) | lr[0] as usize;
+    let right = ((lr[2] as usize) << 4) | (lr[1] as usize >> 4);
+    Some((left, right))
+}
+
+fn read_u16(bytes: &[u8], at: usize) -> Option<u16> {
+    Some(u16::from_le_bytes(bytes.get(at..at + 2)?.try_into().ok()?))
+}
+
+fn read_u32(bytes: &[u8], at: usize) -> Option<u32> {
+    Some(u32::from_le_bytes(bytes.get(at..at + 4)?.try_into().ok()?))
+}
+
+fn read_u32_be(bytes: &[u8], at: usize) -> Option<u32> {
+    Some(u32::from_be_bytes(bytes.get(at..at + 4)?.try_into().ok()?))
+}
+
+fn read_u64_be(bytes: &[u8], at: usize) -> Option<u64> {
+    Some(u64::from_be_bytes(bytes.get(at..at + 8)?.try_into().ok()?))
+}
+
+/// Read-only view of a table file: memory-mapped on Unix, read into memory elsewhere
+struct Mapping {
+    #[cfg(unix)]
+    ptr: *const u8,
+    #[cfg(unix)]
+    len: usize,
+    #[cfg(not(unix))]
+    bytes: Vec<u8>,
+}
+
+// SAFETY: the mapping is read-only and lives as long as the `Mapping`
+#[cfg(unix)]
+unsafe impl Send for Mapping {}
+#[cfg(unix)]
+unsafe impl Sync for Mapping {}
+
+#[cfg(unix)]
+mod sys {
+    use std::ffi::c_void;
+
+    pub const PROT_READ: i32 = 1;
+    pub const MAP_PRIVATE: i32 = 2;
+
+    unsafe extern "C" {
+        pub fn mmap(
+            addr: *mut c_void,
+            len: usize,
+            prot: i32,
+            flags: i32,
+            fd: i32,
+            offset: i64,
+        ) -> *mut c_void;
+        pub fn munmap(addr: *mut c_void, len: usize) -> i32;
+    }
+}
+
+impl Mapping {
+    #[cfg(unix)]
+    fn open(path: &Path) -> io::Result<Self> {
+        use std::os::unix::io::AsRawFd;
+
+        let file = File::open(path)?;
+        let len = file.metadata()?.len() as usize;
+        if len == 0 {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "empty table"));
+        }
+        // SAFETY: a fresh private read-only mapping of a whole, open file; the file
+        // descriptor may be closed once the mapping exists
+        let ptr = unsafe {
+            sys::mmap(
+                std::ptr::null_mut(),
+                len,
+                sys::PROT_READ,
+                sys::MAP_PRIVATE,
+                file.as_raw_fd(),
+                0,
+            )
+        };
+        if ptr as isize == -1 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(Mapping { ptr: ptr as *const u8, len })
+    }
+
+    #[cfg(not(unix))]
+    fn open(path: &Path) -> io::Result<Self> {
+        Ok(Mapping { bytes: std::fs::read(path)? })
+    }
+
+    fn as_slice(&self) -> &[u8] {
+        #[cfg(unix)]
+        // SAFETY: `ptr` maps `len` readable bytes until `drop`
+        return unsafe { std::slice::from_raw_parts(self.ptr, self.len) };
+        #[cfg(not(unix))]
+        return &self.bytes;
+    }
+}
+
+#[cfg(unix)]
+impl Drop for Mapping {
+    fn drop(&mut self) {
+        // SAFETY: unmaps exactly the region mapped in `open`
+        unsafe {
+            sys::munmap(self.ptr as *mut _, self.len);
+        }
+    }
+}
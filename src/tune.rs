@@ -0,0 +1,361 @@
+// src/tune.rs
+
+//! Texel tuning of the hand-crafted evaluation weights.
+//! Every weight enters the evaluation linearly, so each training position is evaluated
+//! once with `Trace` values to get the coefficient of every weight. Training then only
+//! works on those coefficients: the model score is `sum(coefficient * tapered weight)`
+//! and the loss is the mean squared error between `sigmoid(K * score)` and the game
+//! result, minimised with Adam.
+
+use std::fs;
+use std::ops::{Add, AddAssign, Mul, Neg, Sub, SubAssign};
+use std::thread;
+
+use crate::board::Position;
+use crate::eval::{self, EvalParams, PawnEntry, Score, Weight, DEFAULT_PARAMS, MAX_PHASE};
+
+/// Coefficients of the weights used by one evaluation, as (weight index, count) pairs.
+/// Sums simply collect the pairs; `compact` merges them.
+#[derive(Clone, Debug, Default)]
+pub struct Trace(pub Vec<(u32, i32)>);
+
+impl Trace {
+    /// Sort by weight and merge repeated weights, dropping those that cancel out
+    pub fn compact(mut self) -> Vec<(u32, i32)> {
+        self.0.sort_unstable_by_key(|&(index, _)| index);
+        let mut merged: Vec<(u32, i32)> = Vec::with_capacity(self.0.len());
+        for (index, count) in self.0 {
+            match merged.last_mut() {
+                Some(last) if last.0 == index => last.1 += count,
+                _ => merged.push((index, count)),
+            }
+        }
+        merged.retain(|&(_, count)| count != 0);
+        merged
+    }
+}
+
+impl Weight for Trace {
+    fn zero() -> Self {
+        Trace(Vec::new())
+    }
+}
+
+impl Add for Trace {
+    type Output = Trace;
+
+    fn add(mut self, rhs: Trace) -> Trace {
+        self += rhs;
+        self
+    }
+}
+
+impl AddAssign for Trace {
+    fn add_assign(&mut self, rhs: Trace) {
+        self.0.extend(rhs.0);
+    }
+}
+
+impl Sub for Trace {
+    type Output = Trace;
+
+    fn sub(mut self, rhs: Trace) -> Trace {
+        self -= rhs;
+        self
+    }
+}
+
+impl SubAssign for Trace {
+    fn sub_assign(&mut self, rhs: Trace) {
+        self.0.extend(rhs.0.into_iter().map(|(index, count)| (index, -count)));
+    }
+}
+
+impl Neg for Trace {
+    type Output = Trace;
+
+    fn neg(self) -> Trace {
+        Trace(self.0.into_iter().map(|(index, count)| (index, -count)).collect())
+    }
+}
+
+impl Mul<i32> for Trace {
+    type Output = Trace;
+
+    fn mul(self, rhs: i32) -> Trace {
+        Trace(self.0.into_iter().map(|(index, count)| (index, count * rhs)).collect())
+    }
+}
+
+/// One labelled training position
+#[derive(Clone, Debug)]
+pub struct Sample {
+    /// Non-zero weight coefficients, from White's point of view
+    pub coefficients: Vec<(u32, i32)>,
+    /// Middlegame share of the tapered score (phase / `MAX_PHASE`)
+    pub mg_share: f64,
+    /// Game result from White's point of view: 1, 0.5 or 0
+    pub result: f64,
+}
+
+impl Sample {
+    /// Trace a position with its game result, given the `unit_params`
+    pub fn new(pos: &Position, result: f64, units: &EvalParams<Trace>) -> Self {
+        let mut pawns = PawnEntry::new(pos, units);
+        let trace = eval::evaluate_terms(pos, units, &mut pawns);
+        Sample {
+            coefficients: trace.compact(),
+            mg_share: eval::game_phase(pos) as f64 / MAX_PHASE as f64,
+            result,
+        }
+    }
+
+    /// Model score in centipawns for the given weights
+    pub fn score(&self, weights: &[[f64; 2]]) -> f64 {
+        let (mg, eg) = self.coefficients.iter().fold((0.0, 0.0), |(mg, eg), &(index, count)| {
+            let weight = weights[index as usize];
+            (mg + count as f64 * weight[0], eg + count as f64 * weight[1])
+        });
+        mg * self.mg_share + eg * (1.0 - self.mg_share)
+    }
+}
+
+/// Parameters whose values are the trace of their own index; built once per data set,
+/// since every sample is traced with them
+pub fn unit_params() -> EvalParams<Trace> {
+    let mut index = 0;
+    DEFAULT_PARAMS.map(|_| {
+        index += 1;
+        Trace(vec![(index - 1, 1)])
+    })
+}
+
+/// Parse one line of a labelled EPD/FEN file.
+/// The position is the first four fields (an optional halfmove clock and fullmove
+/// number are skipped); the result is the first of `1-0`, `0-1`, `1/2-1/2`, `[1.0]`,
+/// `[0.5]` or `[0.0]` (also without decimals) found after them.
+pub fn parse_line(line: &str) -> Option<(Position, f64)> {
+    let fields: Vec<&str> = line.split_whitespace().collect();
+    if fields.len() < 5 {
+        return None;
+    }
+    let pos = Position::from_fen(&fields[..4].join(" ")).ok()?;
+    let rest = fields[4..].join(" ");
+    const LABELS: [(&str, f64); 8] = [
+        ("1/2-1/2", 0.5),
+        ("1-0", 1.0),
+        ("0-1", 0.0),
+        ("[1.0]", 1.0),
+        ("[0.5]", 0.5),
+        ("[0.0]", 0.0),
+        ("[1]", 1.0),
+        ("[0]", 0.0),
+    ];
+    LABELS
+        .iter()
+        .filter_map(|&(label, result)| rest.find(label).map(|at| (at, result)))
+        .min_by_key(|&(at, _)| at)
+        .map(|(_, result)| (pos, result))
+}
+
+/// Sigmoid mapping a score in centipawns to an expected result
+fn sigmoid(k: f64, score: f64) -> f64 {
+    1.0 / (1.0 + 10f64.powf(-k * score / 400.0))
+}
+
+/// Tuner state: weights as floating point (middlegame, endgame) pairs and Adam moments
+pub struct Tuner {
+    pub samples: Vec<Sample>,
+    pub weights: Vec<[f64; 2]>,
+    /// Sigmoid scaling constant
+    pub k: f64,
+    pub learning_rate: f64,
+    threads: usize,
+    momentum: Vec<[f64; 2]>,
+    velocity: Vec<[f64; 2]>,
+    step: i32,
+}
+
+impl Tuner {
+    /// Start from the default weights
+    pub fn new(samples: Vec<Sample>, learning_rate: f64) -> Self {
+        let weights: Vec<[f64; 2]> = DEFAULT_PARAMS
+            .to_vec()
+            .iter()
+            .map(|score| [score.mg as f64, score.eg as f64])
+            .collect();
+        let len = weights.len();
+        Tuner {
+            samples,
+            weights,
+            k: 1.0,
+            learning_rate,
+            threads: thread::available_parallelism().map_or(1, |n| n.get()),
+            momentum: vec![[0.0; 2]; len],
+            velocity: vec![[0.0; 2]; len],
+            step: 0,
+        }
+    }
+
+    /// Mean squared error of the current weights
+    pub fn error(&self) -> f64 {
+        self.error_with(self.k)
+    }
+
+    fn error_with(&self, k: f64) -> f64 {
+        let total: f64 = self.map_chunks(|chunk| {
+            chunk
+                .iter()
+                .map(|sample| (sample.result - sigmoid(k, sample.score(&self.weights))).powi(2))
+                .sum::<f64>()
+        })
+        .into_iter()
+        .sum();
+        total / self.samples.len().max(1) as f64
+    }
+
+    /// Choose `k` minimising the error of the current weights (golden-section search)
+    pub fn fit_k(&mut self) {
+        let ratio = (5f64.sqrt() - 1.0) / 2.0;
+        let (mut low, mut high) = (0.05, 5.0);
+        for _ in 0..40 {
+            let a = high - ratio * (high - low);
+            let b = low + ratio * (high - low);
+            if self.error_with(a) < self.error_with(b) {
+                high = b;
+            } else {
+                low = a;
+            }
+        }
+        self.k = (low + high) / 2.0;
+    }
+
+    /// One Adam step over the full data set
+    pub fn epoch(&mut self) {
+        const BETA1: f64 = 0.9;
+        const BETA2: f64 = 0.999;
+        const EPSILON: f64 = 1e-8;
+
+        let gradient = self.gradient();
+        self.step += 1;
+        let correction1 = 1.0 - BETA1.powi(self.step);
+        let correction2 = 1.0 - BETA2.powi(self.step);
+        let moments = self.momentum.iter_mut().flatten().zip(self.velocity.iter_mut().flatten());
+        let weights = self.weights.iter_mut().flatten();
+        for ((grad, (m, v)), weight) in gradient.iter().flatten().zip(moments).zip(weights) {
+            *m = BETA1 * *m + (1.0 - BETA1) * grad;
+            *v = BETA2 * *v + (1.0 - BETA2) * grad * grad;
+            *weight -= self.learning_rate * (*m / correction1) / ((*v / correction2).sqrt() + EPSILON);
+        }
+    }
+
+    /// Gradient of the error with respect to every weight
+    fn gradient(&self) -> Vec<[f64; 2]> {
+        let len = self.weights.len();
+        let scale = self.k * 10f64.ln() / 400.0;
+        let partials = self.map_chunks(|chunk| {
+            let mut gradient = vec![[0.0; 2]; len];
+            for sample in chunk {
+                let predicted = sigmoid(self.k, sample.score(&self.weights));
+                let delta =
+                    -2.0 * (sample.result - predicted) * predicted * (1.0 - predicted) * scale;
+                for &(index, count) in &sample.coefficients {
+                    let g = &mut gradient[index as usize];
+                    g[0] += delta * count as f64 * sample.mg_share;
+                    g[1] += delta * count as f64 * (1.0 - sample.mg_share);
+                }
+            }
+            gradient
+        });
+
+        let n = self.samples.len().max(1) as f64;
+        let mut gradient = vec![[0.0; 2]; len];
+        for partial in partials {
+            for (total, part) in gradient.iter_mut().zip(partial) {
+                total[0] += part[0] / n;
+                total[1] += part[1] / n;
+            }
+        }
+        gradient
+    }
+
+    /// Run `f` over the samples split across the worker threads
+    fn map_chunks<R: Send>(&self, f: impl Fn(&[Sample]) -> R + Sync) -> Vec<R> {
+        let chunk = self.samples.len().div_ceil(self.threads).max(1);
+        thread::scope(|scope| {
+            let handles: Vec<_> =
+                self.samples.chunks(chunk).map(|part| scope.spawn(|| f(part))).collect();
+            handles.into_iter().map(|handle| handle.join().unwrap()).collect()
+        })
+    }
+
+    /// Current weights rounded to integers
+    pub fn params(&self) -> EvalParams {
+        let scores: Vec<Score> = self
+            .weights
+            .iter()
+            .map(|w| Score::new(w[0].round() as i32, w[1].round() as i32))
+            .collect();
+        DEFAULT_PARAMS.from_values(&scores)
+    }
+}
+
+const USAGE: &str =
+    "usage: tune <positions.epd> [--epochs N] [--lr RATE] [--k K] [--out FILE] [--limit N]";
+
+/// Command line entry point of the `tune` binary
+pub fn main(args: &[String]) -> Result<(), String> {
+    let mut path = None;
+    let mut epochs = 1000;
+    let mut learning_rate = 1.0;
+    let mut fixed_k = None;
+    let mut out = String::from("weights.rs");
+    let mut limit = usize::MAX;
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or(format!("missing value for {}\n{}", arg, USAGE));
+        match arg.as_str() {
+            "--epochs" => epochs = value()?.parse().map_err(|_| "invalid --epochs")?,
+            "--lr" => learning_rate = value()?.parse().map_err(|_| "invalid --lr")?,
+            "--k" => fixed_k = Some(value()?.parse().map_err(|_| "invalid --k")?),
+            "--out" => out = value()?.clone(),
+            "--limit" => limit = value()?.parse().map_err(|_| "invalid --limit")?,
+            _ if path.is_none() && !arg.starts_with("--") => path = Some(arg.clone()),
+            _ => return Err(USAGE.to_string()),
+        }
+    }
+    let path = path.ok_or(USAGE)?;
+
+    let text = fs::read_to_string(&path).map_err(|e| format!("{}: {}", path, e))?;
+    let mut skipped = 0;
+    let mut samples = Vec::new();
+    let units = unit_params();
+    for line in text.lines().filter(|line| !line.trim().is_empty()).take(limit) {
+        match parse_line(line) {
+            Some((pos, result)) => samples.push(Sample::new(&pos, result, &units)),
+            None => skipped += 1,
+        }
+    }
+    println!("{} positions loaded, {} lines skipped", samples.len(), skipped);
+    if samples.is_empty() {
+        return Err("no labelled positions".to_string());
+    }
+
+    let mut tuner = Tuner::new(samples, learning_rate);
+    match fixed_k {
+        Some(k) => tuner.k = k,
+        None => tuner.fit_k(),
+    }
+    println!("K = {:.4}, initial error {:.6}", tuner.k, tuner.error());
+
+    for epoch in 1..=epochs {
+        tuner.epoch();
+        if epoch % 50 == 0 || epoch == epochs {
+            println!("epoch {} error {:.6}", epoch, tuner.error());
+            fs::write(&out, tuner.params().to_rust()).map_err(|e| format!("{}: {}", out, e))?;
+        }
+    }
+    println!("weights written to {}", out);
+    Ok(())
+}
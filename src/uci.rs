@@ -0,0 +1,400 @@
+// src/uci.rs
+
+//! Universal Chess Interface front end.
+//! Commands are read from stdin on the calling thread; `go` starts the search on a
+//! separate thread so `stop`, `ponderhit` and `isready` are answered immediately.
+//! Besides the UCI commands, `d` prints the current position as FEN for debugging.
+
+use std::io::{self, BufRead, Write};
+use std::sync::atomic::Ordering;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::thread::{self, JoinHandle};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::board::zobrist::splitmix64;
+use crate::board::{Move, Position};
+use crate::book::{Book, BookSelection};
+#[cfg(feature = "nnue")]
+use crate::eval::nnue::Network;
+use crate::eval::PawnTable;
+use crate::search::tt::{DEFAULT_HASH_MB, MAX_HASH_MB};
+use crate::search::{self, Output, SearchLimits, SearchSignals, TranspositionTable};
+use crate::syzygy::Tablebases;
+
+const ENGINE_NAME: &str = "chessr";
+const ENGINE_AUTHOR: &str = "the chessr developers";
+
+/// Largest accepted `Threads` value
+const MAX_THREADS: usize = 256;
+
+/// Default and largest `MoveOverhead` in milliseconds
+const DEFAULT_MOVE_OVERHEAD: u64 = 10;
+const MAX_MOVE_OVERHEAD: u64 = 5000;
+
+/// Write one line to an `Output`, like `println!`
+macro_rules! reply {
+    ($out:expr, $($arg:tt)*) => {{
+        let mut out = $out.lock().unwrap_or_else(PoisonError::into_inner);
+        // Nothing sensible is left to do if the GUI has gone away
+        let _ = writeln!(out, $($arg)*);
+        let _ = out.flush();
+    }};
+}
+
+/// UCI session state
+pub struct Uci {
+    /// Replies to the GUI
+    out: Output,
+    /// Position set by the last `position` command
+    position: Position,
+    /// Flags shared with the running search
+    signals: Arc<SearchSignals>,
+    /// Thread of the running search, if any
+    search_thread: Option<JoinHandle<()>>,
+    /// Transposition table shared with the search
+    tt: Arc<TranspositionTable>,
+    /// Pawn structure caches, one per search thread (`Threads`), kept like the TT
+    pawns: Arc<Mutex<Vec<PawnTable>>>,
+    /// Milliseconds kept back on every move for GUI and network lag
+    move_overhead: u64,
+    /// Play moves from `book` while it has any (`OwnBook`)
+    own_book: bool,
+    /// Opening book loaded with `BookFile`
+    book: Option<Book>,
+    book_selection: BookSelection,
+    /// Endgame tables found in `SyzygyPath`
+    tablebases: Option<Arc<Tablebases>>,
+    /// Network selected with `EvalFile`; the hand-crafted evaluation is used without one
+    #[cfg(feature = "nnue")]
+    network: Option<Arc<Network>>,
+}
+
+/// Read UCI commands from stdin until `quit` or end of input
+pub fn run() {
+    let mut uci = Uci::new();
+    for line in io::stdin().lock().lines() {
+        let Ok(line) = line else { break };
+        if !uci.handle_command(&line) {
+            break;
+        }
+    }
+    uci.stop_search();
+}
+
+impl Uci {
+    /// Session replying on stdout
+    pub fn new() -> Self {
+        Uci::with_output(io::stdout())
+    }
+
+    /// Session replying to `out`
+    pub fn with_output(out: impl Write + Send + 'static) -> Self {
+        let out: Output = Arc::new(Mutex::new(out));
+        let signals = SearchSignals { output: Some(Arc::clone(&out)), ..Default::default() };
+        Uci {
+            out,
+            position: Position::startpos(),
+            signals: Arc::new(signals),
+            search_thread: None,
+            tt: Arc::new(TranspositionTable::new(DEFAULT_HASH_MB)),
+            pawns: Arc::new(Mutex::new(vec![PawnTable::new()])),
+            move_overhead: DEFAULT_MOVE_OVERHEAD,
+            own_book: false,
+            book: None,
+            book_selection: BookSelection::default(),
+            tablebases: None,
+            #[cfg(feature = "nnue")]
+            network: None,
+        }
+    }
+
+    /// Handle one command line; returns false on `quit`
+    pub fn handle_command(&mut self, line: &str) -> bool {
+        let tokens: Vec<&str> = line.split_whitespace().collect();
+        let Some((&command, args)) = tokens.split_first() else {
+            return true;
+        };
+
+        match command {
+            "uci" => {
+                reply!(self.out, "id name {} {}", ENGINE_NAME, env!("CARGO_PKG_VERSION"));
+                reply!(self.out, "id author {}", ENGINE_AUTHOR);
+                reply!(
+                    self.out,
+                    "option name Hash type spin default {} min 1 max {}",
+                    DEFAULT_HASH_MB, MAX_HASH_MB
+                );
+                reply!(
+                    self.out,
+                    "option name Threads type spin default 1 min 1 max {}",
+                    MAX_THREADS
+                );
+                reply!(
+                    self.out,
+                    "option name MoveOverhead type spin default {} min 0 max {}",
+                    DEFAULT_MOVE_OVERHEAD, MAX_MOVE_OVERHEAD
+                );
+                reply!(self.out, "option name OwnBook type check default false");
+                reply!(self.out, "option name BookFile type string default <empty>");
+                reply!(
+                    self.out,
+                    "option name BookSelection type combo default Weighted var Weighted var Best"
+                );
+                reply!(self.out, "option name SyzygyPath type string default <empty>");
+                #[cfg(feature = "nnue")]
+                reply!(self.out, "option name EvalFile type string default <empty>");
+                reply!(self.out, "uciok");
+            }
+            "isready" => reply!(self.out, "readyok"),
+            "ucinewgame" => {
+                self.stop_search();
+                self.position = Position::startpos();
+                self.tt.clear();
+                let mut pawns = self.pawns.lock().unwrap_or_else(PoisonError::into_inner);
+                pawns.iter_mut().for_each(PawnTable::clear);
+            }
+            "position" => {
+                self.stop_search();
+                if let Err(msg) = self.set_position(args) {
+                    reply!(self.out, "info string {}", msg);
+                }
+            }
+            "go" => self.go(args),
+            "stop" => self.stop_search(),
+            "ponderhit" => self.signals.ponder.store(false, Ordering::Relaxed),
+            "setoption" => self.set_option(args),
+            // Debug extension, not part of UCI
+            "d" => reply!(self.out, "{}", self.position.to_fen()),
+            "quit" => return false,
+            _ => reply!(self.out, "info string unknown command: {}", command),
+        }
+        true
+    }
+
+    /// `position [startpos | fen <fen>] [moves <move>...]`
+    fn set_position(&mut self, args: &[&str]) -> Result<(), String> {
+        let moves_at = args.iter().position(|&t| t == "moves").unwrap_or(args.len());
+        let mut pos = match args.first() {
+            Some(&"startpos") => Position::startpos(),
+            Some(&"fen") => {
+                Position::from_fen(&args[1..moves_at].join(" ")).map_err(|e| e.to_string())?
+            }
+            _ => return Err("expected 'startpos' or 'fen'".to_string()),
+        };
+
+        for text in args.iter().skip(moves_at + 1) {
+            let mv = parse_move(&pos, text).ok_or_else(|| format!("illegal move: {}", text))?;
+            pos.make_move(mv);
+        }
+
+        self.position = pos;
+        Ok(())
+    }
+
+    /// `go` with any of the UCI search limits, or `go perft <depth>`
+    fn go(&mut self, args: &[&str]) {
+        self.stop_search();
+
+        if args.first() == Some(&"perft") {
+            let depth = args.get(1).and_then(|d| d.parse().ok()).unwrap_or(1);
+            let mut total = 0;
+            for (mv, nodes) in self.position.divide(depth) {
+                reply!(self.out, "{}: {}", mv, nodes);
+                total += nodes;
+            }
+            reply!(self.out, "\nNodes searched: {}", total);
+            return;
+        }
+
+        let mut limits = parse_limits(&self.position, args);
+        limits.move_overhead = self.move_overhead;
+        if let Some(mv) = self.book_move(&limits) {
+            reply!(self.out, "bestmove {}", mv);
+            return;
+        }
+        self.signals.stop.store(false, Ordering::Relaxed);
+        self.signals.ponder.store(limits.ponder, Ordering::Relaxed);
+
+        #[allow(unused_mut)]
+        let mut position = self.position.clone();
+        #[cfg(feature = "nnue")]
+        position.set_network(self.network.clone());
+        let signals = Arc::clone(&self.signals);
+        let tt = Arc::clone(&self.tt);
+        let pawns = Arc::clone(&self.pawns);
+        let tablebases = self.tablebases.clone();
+        let out = Arc::clone(&self.out);
+        self.search_thread = Some(thread::spawn(move || {
+            let tablebases = tablebases.as_deref();
+            let mut pawns = pawns.lock().unwrap_or_else(PoisonError::into_inner);
+            let result = search::think(&position, &limits, &signals, &tt, &mut pawns, tablebases);
+            match result.ponder_move {
+                Some(ponder) => reply!(out, "bestmove {} ponder {}", result.best_move, ponder),
+                None => reply!(out, "bestmove {}", result.best_move),
+            }
+        }));
+    }
+
+    /// `setoption name <id> [value <x>]`
+    fn set_option(&mut self, args: &[&str]) {
+        let value_at = args.iter().position(|&t| t == "value").unwrap_or(args.len());
+        let name = args.get(1..value_at).unwrap_or_default().join(" ");
+        let value = args.get(value_at + 1..).unwrap_or_default().join(" ");
+
+        match name.to_ascii_lowercase().as_str() {
+            "hash" => match value.parse::<usize>() {
+                Ok(mb) => {
+                    self.stop_search();
+                    // Drop the old table before allocating the new one
+                    self.tt = Arc::new(TranspositionTable::new(1));
+                    self.tt = Arc::new(TranspositionTable::new(mb.clamp(1, MAX_HASH_MB)));
+                }
+                Err(_) => reply!(self.out, "info string invalid Hash value: {}", value),
+            },
+            "threads" => match value.parse::<usize>() {
+                Ok(threads) => {
+                    self.stop_search();
+                    let mut pawns = self.pawns.lock().unwrap_or_else(PoisonError::into_inner);
+                    pawns.resize_with(threads.clamp(1, MAX_THREADS), PawnTable::new);
+                }
+                Err(_) => reply!(self.out, "info string invalid Threads value: {}", value),
+            },
+            "moveoverhead" => match value.parse::<u64>() {
+                Ok(ms) => self.move_overhead = ms.min(MAX_MOVE_OVERHEAD),
+                Err(_) => reply!(self.out, "info string invalid MoveOverhead value: {}", value),
+            },
+            "ownbook" => self.own_book = value.eq_ignore_ascii_case("true"),
+            "bookfile" => {
+                if value.is_empty() || value == "<empty>" {
+                    self.book = None;
+                    return;
+                }
+                match Book::load(&value) {
+                    Ok(book) => {
+                        let entries = book.len();
+                        reply!(self.out, "info string loaded book {} ({} entries)", value, entries);
+                        self.book = Some(book);
+                    }
+                    Err(err) => reply!(self.out, "info string {}: {}", value, err),
+                }
+            }
+            "bookselection" => match value.to_ascii_lowercase().as_str() {
+                "weighted" => self.book_selection = BookSelection::Weighted,
+                "best" => self.book_selection = BookSelection::Best,
+                _ => reply!(self.out, "info string invalid BookSelection value: {}", value),
+            },
+            "syzygypath" => {
+                self.stop_search();
+                if value.is_empty() || value == "<empty>" {
+                    self.tablebases = None;
+                    return;
+                }
+                let tablebases = Tablebases::open(&value);
+                reply!(
+                    self.out,
+                    "info string found {} tablebases with up to {} pieces",
+                    tablebases.len(),
+                    tablebases.max_pieces()
+                );
+                self.tablebases = (!tablebases.is_empty()).then(|| Arc::new(tablebases));
+            }
+            #[cfg(feature = "nnue")]
+            "evalfile" => {
+                self.stop_search();
+                if value.is_empty() || value == "<empty>" {
+                    self.network = None;
+                    return;
+                }
+                match Network::load(&value) {
+                    Ok(net) => {
+                        reply!(self.out, "info string loaded network {} ({:?})", value, net);
+                        self.network = Some(Arc::new(net));
+                    }
+                    Err(err) => reply!(self.out, "info string {}: {}", value, err),
+                }
+            }
+            _ => reply!(self.out, "info string unknown option: {} = {}", name, value),
+        }
+    }
+
+    /// Move to play from the opening book instead of searching, if any. Searches the
+    /// GUI has to stop itself (`infinite`, `ponder`) or restricts to `searchmoves` always run.
+    fn book_move(&self, limits: &SearchLimits) -> Option<Move> {
+        if !self.own_book || limits.infinite || limits.ponder || !limits.searchmoves.is_empty() {
+            return None;
+        }
+        let nanos = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_nanos());
+        let random = splitmix64(nanos as u64).0;
+        self.book.as_ref()?.choose(&self.position, self.book_selection, random)
+    }
+
+    /// Stop the running search (if any) and wait for it to report its move
+    pub fn stop_search(&mut self) {
+        if let Some(handle) = self.search_thread.take() {
+            self.signals.stop.store(true, Ordering::Relaxed);
+            self.signals.ponder.store(false, Ordering::Relaxed);
+            let _ = handle.join();
+        }
+    }
+}
+
+impl Default for Uci {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Find the legal move written in UCI long algebraic notation
+pub fn parse_move(pos: &Position, text: &str) -> Option<Move> {
+    pos.parse_lan(text).ok()
+}
+
+/// Parse the arguments of `go` into search limits
+pub fn parse_limits(pos: &Position, args: &[&str]) -> SearchLimits {
+    let mut limits = SearchLimits::default();
+    let mut i = 0;
+    while i < args.len() {
+        let next = args.get(i + 1).copied().unwrap_or("");
+        match args[i] {
+            "wtime" => limits.wtime = parse_time(next),
+            "btime" => limits.btime = parse_time(next),
+            "winc" => limits.winc = parse_time(next),
+            "binc" => limits.binc = parse_time(next),
+            "movestogo" => limits.movestogo = next.parse().ok(),
+            "depth" => limits.depth = next.parse().ok(),
+            "nodes" => limits.nodes = next.parse().ok(),
+            "mate" => limits.mate = next.parse().ok(),
+            "movetime" => limits.movetime = parse_time(next),
+            "infinite" => {
+                limits.infinite = true;
+                i += 1;
+                continue;
+            }
+            "ponder" => {
+                limits.ponder = true;
+                i += 1;
+                continue;
+            }
+            "searchmoves" => {
+                // Moves run until the next keyword (or the end)
+                i += 1;
+                while let Some(mv) = args.get(i).and_then(|t| parse_move(pos, t)) {
+                    limits.searchmoves.push(mv);
+                    i += 1;
+                }
+                continue;
+            }
+            _ => {
+                i += 1;
+                continue;
+            }
+        }
+        i += 2;
+    }
+    limits
+}
+
+/// Parse a time in milliseconds; GUIs sometimes send negative values when flagging
+fn parse_time(text: &str) -> Option<u64> {
+    text.parse::<i64>().ok().map(|ms| ms.max(0) as u64)
+}
@@ -0,0 +1,46 @@
+// tests/attacks.rs
+
+use chessr::board::attacks::{king_attacks, knight_attacks, pawn_attacks};
+use chessr::board::squares::*;
+use chessr::board::{sides, BitBoard};
+
+fn board(squares: &[usize]) -> BitBoard {
+    let mut bb = BitBoard::empty();
+    for &square in squares {
+        bb.set_bit(square);
+    }
+    bb
+}
+
+#[test]
+fn knight_attacks_stay_on_the_board() {
+    assert_eq!(knight_attacks(A1), board(&[B3, C2]));
+    assert_eq!(knight_attacks(H8), board(&[G6, F7]));
+    assert_eq!(knight_attacks(H1), board(&[G3, F2]));
+    assert_eq!(knight_attacks(A8), board(&[B6, C7]));
+    assert_eq!(knight_attacks(B2), board(&[A4, C4, D3, D1]));
+    assert_eq!(knight_attacks(G4), board(&[E3, E5, F2, F6, H2, H6]));
+    assert_eq!(knight_attacks(D4).popcount(), 8);
+}
+
+#[test]
+fn king_attacks_stay_on_the_board() {
+    assert_eq!(king_attacks(A1), board(&[A2, B1, B2]));
+    assert_eq!(king_attacks(H8), board(&[G8, G7, H7]));
+    assert_eq!(king_attacks(H4), board(&[G3, G4, G5, H3, H5]));
+    assert_eq!(king_attacks(E1), board(&[D1, D2, E2, F2, F1]));
+    assert_eq!(king_attacks(E4).popcount(), 8);
+}
+
+#[test]
+fn pawn_attacks_do_not_wrap_around_files() {
+    assert_eq!(pawn_attacks(sides::WHITE, A2), board(&[B3]));
+    assert_eq!(pawn_attacks(sides::WHITE, H2), board(&[G3]));
+    assert_eq!(pawn_attacks(sides::WHITE, E4), board(&[D5, F5]));
+    assert_eq!(pawn_attacks(sides::BLACK, A7), board(&[B6]));
+    assert_eq!(pawn_attacks(sides::BLACK, H7), board(&[G6]));
+    assert_eq!(pawn_attacks(sides::BLACK, E5), board(&[D4, F4]));
+    // Nothing beyond the last rank
+    assert!(pawn_attacks(sides::WHITE, A8).is_empty());
+    assert!(pawn_attacks(sides::BLACK, H1).is_empty());
+}
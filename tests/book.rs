@@ -0,0 +1,120 @@
+// tests/book.rs
+
+use chessr::board::Position;
+use chessr::book::{encode_move, Book, BookEntry, BookSelection};
+
+fn play(moves: &[&str]) -> Position {
+    let mut pos = Position::startpos();
+    for uci in moves {
+        let mv = *pos.generate_legal_moves().iter().find(|mv| mv.to_string() == *uci).unwrap();
+        pos.make_move(mv);
+    }
+    pos
+}
+
+fn entry(pos: &Position, uci: &str, weight: u16) -> BookEntry {
+    let mv = *pos.generate_legal_moves().iter().find(|mv| mv.to_string() == uci).unwrap();
+    BookEntry { key: pos.polyglot_key(), mv: encode_move(mv), weight, learn: 0 }
+}
+
+fn book(entries: &[BookEntry]) -> Book {
+    let bytes: Vec<u8> = entries.iter().flat_map(|entry| entry.to_bytes()).collect();
+    Book::from_bytes(&bytes).unwrap()
+}
+
+#[test]
+fn published_keys() {
+    // Reference keys from the Polyglot book format specification
+    let cases: [(&[&str], u64); 9] = [
+        (&[], 0x463B96181691FC9C),
+        (&["e2e4"], 0x823C9B50FD114196),
+        (&["e2e4", "d7d5"], 0x0756B94461C50FB0),
+        (&["e2e4", "d7d5", "e4e5"], 0x662FAFB965DB29D4),
+        (&["e2e4", "d7d5", "e4e5", "f7f5"], 0x22A48B5A8E47FF78),
+        (&["e2e4", "d7d5", "e4e5", "f7f5", "e1e2"], 0x652A607CA3F242C1),
+        (&["e2e4", "d7d5", "e4e5", "f7f5", "e1e2", "e8f7"], 0x00FDD303C946BDD9),
+        (&["a2a4", "b7b5", "h2h4", "b5b4", "c2c4"], 0x3C8123EA7B067637),
+        (&["a2a4", "b7b5", "h2h4", "b5b4", "c2c4", "b4c3", "a1a3"], 0x5C3F9B829B279560),
+    ];
+    for (moves, key) in cases {
+        assert_eq!(play(moves).polyglot_key(), key, "{:?}", moves);
+    }
+}
+
+#[test]
+fn finds_moves_under_published_keys() {
+    // Entries as a Polyglot tool writes them, keyed by the reference keys rather than
+    // by anything computed here: 1. e4 (e2e4) and 1. d4 (d2d4), then 1... d5 (d7d5)
+    let entries = [
+        BookEntry { key: 0x463B96181691FC9C, mv: (12 << 6) | 28, weight: 7, learn: 0 },
+        BookEntry { key: 0x463B96181691FC9C, mv: (11 << 6) | 27, weight: 2, learn: 0 },
+        BookEntry { key: 0x823C9B50FD114196, mv: (51 << 6) | 35, weight: 1, learn: 0 },
+    ];
+    let book = book(&entries);
+    let moves: Vec<String> =
+        book.moves(&Position::startpos()).iter().map(|(mv, _)| mv.to_string()).collect();
+    assert_eq!(moves, ["e2e4", "d2d4"]);
+    let after_e4 = play(&["e2e4"]);
+    assert_eq!(book.choose(&after_e4, BookSelection::Best, 0).unwrap().to_string(), "d7d5");
+    assert!(book.moves(&play(&["e2e4", "d7d5"])).is_empty());
+}
+
+#[test]
+fn en_passant_file_needs_a_capturing_pawn() {
+    // No black pawn can take on e3, so the key ignores the en passant square
+    let pos = play(&["e2e4"]);
+    let plain = Position::from_fen("rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq - 0 1");
+    assert_eq!(pos.polyglot_key(), plain.unwrap().polyglot_key());
+
+    // After f7f5 the pawn on e5 can, so it counts
+    let pos = play(&["e2e4", "d7d5", "e4e5", "f7f5"]);
+    let plain = Position::from_fen("rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq - 0 3");
+    assert_ne!(pos.polyglot_key(), plain.unwrap().polyglot_key());
+}
+
+#[test]
+fn chooses_among_book_moves() {
+    let start = Position::startpos();
+    let after_e4 = play(&["e2e4"]);
+    let book = book(&[
+        entry(&after_e4, "c7c5", 5),
+        entry(&start, "d2d4", 3),
+        entry(&start, "e2e4", 10),
+        entry(&start, "g1f3", 0),
+    ]);
+    assert_eq!(book.len(), 4);
+    assert_eq!(book.moves(&start).len(), 3);
+
+    let best = book.choose(&start, BookSelection::Best, 0).unwrap();
+    assert_eq!(best.to_string(), "e2e4");
+    assert_eq!(book.choose(&after_e4, BookSelection::Best, 0).unwrap().to_string(), "c7c5");
+
+    // Weighted picks follow the weights and never play a zero-weight move
+    let mut picks = Vec::new();
+    for random in 0..13 {
+        picks.push(book.choose(&start, BookSelection::Weighted, random).unwrap().to_string());
+    }
+    assert_eq!(picks.iter().filter(|mv| *mv == "d2d4").count(), 3);
+    assert_eq!(picks.iter().filter(|mv| *mv == "e2e4").count(), 10);
+
+    assert!(book.choose(&play(&["d2d4"]), BookSelection::Weighted, 7).is_none());
+}
+
+#[test]
+fn castling_and_promotion_encoding() {
+    let pos = Position::from_fen("r3k2r/1P6/8/8/8/8/8/R3K2R w KQkq - 0 1").unwrap();
+    let castle = entry(&pos, "e1g1", 1);
+    assert_eq!(castle.mv, (4 << 6) | 7); // e1h1
+    assert_eq!(entry(&pos, "e1c1", 1).mv, 4 << 6); // e1a1
+    assert_eq!(entry(&pos, "b7a8q", 1).mv, (4 << 12) | (49 << 6) | 56);
+
+    let book = book(&[castle]);
+    assert_eq!(book.choose(&pos, BookSelection::Best, 0).unwrap().to_string(), "e1g1");
+}
+
+#[test]
+fn rejects_truncated_books() {
+    let bytes = entry(&Position::startpos(), "e2e4", 1).to_bytes();
+    assert!(Book::from_bytes(&bytes[..15]).is_err());
+    assert!(Book::from_bytes(&[]).unwrap().is_empty());
+}
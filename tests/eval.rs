@@ -0,0 +1,83 @@
+// tests/eval.rs
+
+use chessr::board::Position;
+use chessr::eval::{
+    evaluate, evaluate_cached, evaluate_with, game_phase, PawnTable, DEFAULT_PARAMS, MAX_PHASE,
+};
+
+/// Mirror a FEN vertically and swap colours
+fn mirror(fen: &str) -> String {
+    let fields: Vec<&str> = fen.split_whitespace().collect();
+    let swap = |c: char| {
+        if c.is_ascii_uppercase() { c.to_ascii_lowercase() } else { c.to_ascii_uppercase() }
+    };
+    let board: Vec<String> =
+        fields[0].split('/').rev().map(|rank| rank.chars().map(swap).collect()).collect();
+    let side = if fields[1] == "w" { "b" } else { "w" };
+    let mut castling: Vec<char> = fields[2].chars().map(swap).collect();
+    castling.sort();
+    let castling: String = castling.into_iter().collect();
+    format!("{} {} {} - 0 1", board.join("/"), side, castling)
+}
+
+#[test]
+fn eval_is_symmetric() {
+    let fens = [
+        "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq - 0 1",
+        "r1bq1rk1/pp2bppp/2n1pn2/3p4/2PP4/2N1PN2/PP3PPP/R2QKB1R w KQ - 0 8",
+        "8/5pk1/6p1/3P4/1p6/1P4P1/5PK1/8 w - - 0 40",
+        "4k3/8/8/8/8/8/8/RN2K3 b - - 0 1",
+    ];
+    for fen in fens {
+        let pos = Position::from_fen(fen).unwrap();
+        let mirrored = Position::from_fen(&mirror(fen)).unwrap();
+        assert_eq!(evaluate(&pos), evaluate(&mirrored), "{}", fen);
+    }
+}
+
+#[test]
+fn eval_prefers_material_and_passed_pawns() {
+    // Startpos is balanced apart from the tempo bonus
+    assert!(evaluate(&Position::startpos()).abs() < 30);
+    // An extra rook is worth a lot, from either side's point of view
+    let white = Position::from_fen("4k3/8/8/8/8/8/8/R3K3 w - - 0 1").unwrap();
+    let black = Position::from_fen("4k3/8/8/8/8/8/8/R3K3 b - - 0 1").unwrap();
+    assert!(evaluate(&white) > 400);
+    assert!(evaluate(&black) < -400);
+    // A far advanced passed pawn beats one still blocked by an enemy pawn
+    let passed = Position::from_fen("4k3/p7/3P4/8/8/8/8/4K3 w - - 0 1").unwrap();
+    let blocked = Position::from_fen("4k3/3p4/3P4/8/8/8/8/4K3 w - - 0 1").unwrap();
+    assert!(evaluate(&passed) > evaluate(&blocked));
+}
+
+#[test]
+fn phase_tapers_from_middlegame_to_endgame() {
+    assert_eq!(game_phase(&Position::startpos()), MAX_PHASE);
+    let pawns = Position::from_fen("4k3/pppp4/8/8/8/8/4PPPP/4K3 w - - 0 1").unwrap();
+    assert_eq!(game_phase(&pawns), 0);
+}
+
+#[test]
+fn pawn_cache_matches_uncached_eval() {
+    let mut table = PawnTable::new();
+    let mut pos = Position::startpos();
+    // Walk through a game so entries are both stored and hit
+    for uci in ["e2e4", "c7c5", "g1f3", "d7d6", "d2d4", "c5d4", "f3d4", "g8f6", "b1c3", "a7a6"] {
+        assert_eq!(
+            evaluate_cached(&pos, &DEFAULT_PARAMS, &mut table),
+            evaluate_with(&pos, &DEFAULT_PARAMS)
+        );
+        let mv = *pos.generate_legal_moves().iter().find(|mv| mv.to_string() == uci).unwrap();
+        pos.make_move(mv);
+        assert_eq!(
+            evaluate_cached(&pos, &DEFAULT_PARAMS, &mut table),
+            evaluate_with(&pos, &DEFAULT_PARAMS)
+        );
+    }
+}
+
+#[test]
+fn default_weights_file_is_generated() {
+    // weights.rs is written by the tuner; keep it in the generator's exact format
+    assert_eq!(DEFAULT_PARAMS.to_rust(), include_str!("../src/eval/weights.rs"));
+}
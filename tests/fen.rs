@@ -0,0 +1,63 @@
+// tests/fen.rs
+
+use chessr::board::fen::STARTPOS_FEN;
+use chessr::board::{FenError, Position};
+
+#[test]
+fn fen_round_trips() {
+    for fen in [
+        STARTPOS_FEN,
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        "rnbqkbnr/pp1ppppp/8/2p5/4P3/8/PPPP1PPP/RNBQKBNR w KQkq c6 0 2",
+        "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 b - - 37 58",
+    ] {
+        assert_eq!(Position::from_fen(fen).unwrap().to_fen(), fen);
+    }
+}
+
+#[test]
+fn fen_defaults_missing_clocks() {
+    let pos = Position::from_fen("4k3/8/8/8/8/8/8/4K3 w - -").unwrap();
+    assert_eq!(pos.to_fen(), "4k3/8/8/8/8/8/8/4K3 w - - 0 1");
+}
+
+#[test]
+fn fen_errors_name_the_field() {
+    assert!(matches!(Position::from_fen(""), Err(FenError::MissingField(_))));
+    assert!(matches!(
+        Position::from_fen("4k3/8/8/8/8/8/8/4K3 x - - 0 1"),
+        Err(FenError::SideToMove(_))
+    ));
+    assert!(matches!(
+        Position::from_fen("4k3/8/8/8/8/8/8/4K3 w KX - 0 1"),
+        Err(FenError::Castling(_))
+    ));
+    assert!(matches!(
+        Position::from_fen("4k3/8/8/8/8/8/8/4K3 w - e4 0 1"),
+        Err(FenError::EnPassant(_))
+    ));
+    assert!(matches!(
+        Position::from_fen("4k3/8/8/8/8/8/8/4K3 w - - x 1"),
+        Err(FenError::HalfmoveClock(_))
+    ));
+    assert!(matches!(
+        Position::from_fen("4k3/8/8/8/8/8/8/4K3 w - - 0 0"),
+        Err(FenError::FullmoveNumber(_))
+    ));
+    assert!(matches!(
+        Position::from_fen("4k3/8/8/8/8/8/4K3 w - - 0 1"),
+        Err(FenError::PiecePlacement(_))
+    ));
+}
+
+#[test]
+fn startpos_matches_fen() {
+    assert_eq!(Position::startpos().to_fen(), STARTPOS_FEN);
+}
+
+#[test]
+fn inconsistent_state_is_dropped() {
+    // No rook on h1 and no black pawn on d5
+    let pos = Position::from_fen("r3k2r/8/8/8/8/8/8/R3K3 w KQkq d6 0 1").unwrap();
+    assert_eq!(pos.to_fen(), "r3k2r/8/8/8/8/8/8/R3K3 w Qkq - 0 1");
+}
@@ -0,0 +1,49 @@
+// tests/magic.rs
+
+use chessr::board::magic::{
+    bishop_attacks, queen_attacks, rook_attacks, slow_bishop_attacks, slow_rook_attacks,
+};
+use chessr::board::BitBoard;
+
+/// Deterministic pseudo-random occupancies
+fn occupancies() -> impl Iterator<Item = BitBoard> {
+    let mut state = 0x1234_5678_9ABC_DEF0u64;
+    (0..200).map(move |i| {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        // Mix sparse and dense boards
+        match i % 3 {
+            0 => BitBoard(state & (state >> 8)),
+            1 => BitBoard(state),
+            _ => BitBoard(state | (state << 3)),
+        }
+    })
+}
+
+#[test]
+fn magic_attacks_match_ray_walk() {
+    for occ in occupancies() {
+        for square in 0..64 {
+            assert_eq!(bishop_attacks(square, occ), slow_bishop_attacks(square, occ));
+            assert_eq!(rook_attacks(square, occ), slow_rook_attacks(square, occ));
+            assert_eq!(
+                queen_attacks(square, occ),
+                slow_bishop_attacks(square, occ) | slow_rook_attacks(square, occ)
+            );
+        }
+    }
+}
+
+#[test]
+fn empty_board_attack_counts() {
+    assert_eq!(rook_attacks(0, BitBoard::empty()).popcount(), 14);
+    assert_eq!(bishop_attacks(0, BitBoard::empty()).popcount(), 7);
+    assert_eq!(bishop_attacks(27, BitBoard::empty()).popcount(), 13);
+}
+
+#[cfg(all(feature = "pext", target_arch = "x86_64"))]
+#[test]
+fn pext_follows_cpu_support() {
+    assert_eq!(chessr::board::magic::uses_pext(), is_x86_feature_detected!("bmi2"));
+}
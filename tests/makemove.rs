@@ -0,0 +1,129 @@
+// tests/makemove.rs
+
+use chessr::board::{sides, Position};
+
+#[test]
+fn unmake_restores_position() {
+    for fen in [
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+        "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3",
+    ] {
+        let mut pos = Position::from_fen(fen).unwrap();
+        for &mv in pos.generate_legal_moves().iter() {
+            pos.make_move(mv);
+            for &reply in pos.generate_legal_moves().iter() {
+                pos.make_move(reply);
+                pos.unmake_move();
+            }
+            pos.unmake_move();
+            assert_eq!(pos.to_fen(), fen, "after {}", mv);
+        }
+    }
+}
+
+#[test]
+fn make_move_updates_state() {
+    let mut pos = Position::startpos();
+    for uci in ["e2e4", "c7c5", "g1f3", "d7d6", "e1e2"] {
+        let mv = *pos.generate_legal_moves().iter().find(|mv| mv.to_string() == uci).unwrap();
+        pos.make_move(mv);
+    }
+    assert_eq!(pos.to_fen(), "rnbqkbnr/pp2pppp/3p4/2p5/4P3/5N2/PPPPKPPP/RNBQ1B1R b kq - 1 3");
+}
+
+fn play(pos: &mut Position, moves: &[&str]) {
+    for uci in moves {
+        let mv = *pos.generate_legal_moves().iter().find(|mv| mv.to_string() == *uci).unwrap();
+        pos.make_move(mv);
+    }
+}
+
+#[test]
+fn zobrist_key_follows_transpositions() {
+    let start = Position::startpos();
+    assert_eq!(start.hash, start.compute_hash());
+
+    let mut a = Position::startpos();
+    play(&mut a, &["g1f3", "g8f6", "f3g1", "f6g8"]);
+    assert_eq!(a.hash, start.hash);
+
+    let mut b = Position::startpos();
+    let mut c = Position::startpos();
+    play(&mut b, &["e2e4", "e7e5", "g1f3"]);
+    play(&mut c, &["g1f3", "e7e5", "e2e4"]);
+    assert_eq!(b.hash, c.hash);
+    assert_eq!(b.hash, Position::from_fen(&b.to_fen()).unwrap().hash);
+
+    // Same placement, different castling rights
+    let mut d = Position::startpos();
+    play(&mut d, &["e2e4", "e7e5", "e1e2", "e8e7", "e2e1", "e7e8"]);
+    assert_ne!(d.hash, b.hash);
+    assert_ne!(d.hash, Position::from_fen(&d.to_fen().replace(" - ", " KQkq ")).unwrap().hash);
+}
+
+#[test]
+fn zobrist_key_agrees_with_fen() {
+    for (moves, fen) in [
+        (&["e2e4", "c7c5"][..], "rnbqkbnr/pp1ppppp/8/2p5/4P3/8/PPPP1PPP/RNBQKBNR w KQkq c6 0 2"),
+        (
+            &["e2e4", "d7d5", "e4e5", "f7f5"][..],
+            "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3",
+        ),
+    ] {
+        let mut pos = Position::startpos();
+        play(&mut pos, moves);
+        let loaded = Position::from_fen(fen).unwrap();
+        assert_eq!(pos.hash, loaded.hash, "{}", fen);
+        assert_eq!(pos.en_passant, loaded.en_passant, "{}", fen);
+        assert_eq!(pos.to_fen(), fen);
+    }
+
+    // The square is kept as written, but only counts when a pawn can capture on it
+    let c6 = "rnbqkbnr/pp1ppppp/8/2p5/4P3/8/PPPP1PPP/RNBQKBNR w KQkq c6 0 2";
+    let plain = Position::from_fen(&c6.replace(" c6 ", " - ")).unwrap();
+    assert_eq!(Position::from_fen(c6).unwrap().hash, plain.hash);
+    let f6 = "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3";
+    let plain = Position::from_fen(&f6.replace(" f6 ", " - ")).unwrap();
+    assert_ne!(Position::from_fen(f6).unwrap().hash, plain.hash);
+}
+
+#[test]
+fn pawn_key_only_tracks_pawns() {
+    let start = Position::startpos();
+    assert_eq!(start.pawn_hash, start.compute_pawn_hash());
+
+    // Piece moves leave the pawn key alone, pawn moves and captures change it
+    let mut a = Position::startpos();
+    play(&mut a, &["g1f3", "b8c6"]);
+    assert_eq!(a.pawn_hash, start.pawn_hash);
+    play(&mut a, &["e2e4", "d7d5"]);
+    assert_ne!(a.pawn_hash, start.pawn_hash);
+    let before = a.pawn_hash;
+    play(&mut a, &["e4d5"]);
+    assert_ne!(a.pawn_hash, before);
+    a.unmake_move();
+    assert_eq!(a.pawn_hash, before);
+}
+
+#[test]
+fn null_move_passes_the_turn() {
+    let fen = "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3";
+    let mut pos = Position::from_fen(fen).unwrap();
+    let (hash, pawn_hash) = (pos.hash, pos.pawn_hash);
+    pos.make_null_move();
+    let passed = Position::from_fen("rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR b KQkq - 1 3");
+    assert_eq!(pos.to_fen(), passed.as_ref().unwrap().to_fen());
+    assert_eq!(pos.hash, passed.unwrap().hash);
+    assert_eq!(pos.pawn_hash, pawn_hash);
+    pos.unmake_null_move();
+    assert_eq!(pos.to_fen(), fen);
+    assert_eq!(pos.hash, hash);
+}
+
+#[test]
+fn non_pawn_material() {
+    let pos = Position::from_fen("4k3/pppp4/8/8/8/8/4P3/4KN2 w - - 0 1").unwrap();
+    assert!(pos.has_non_pawn_material(sides::WHITE));
+    assert!(!pos.has_non_pawn_material(sides::BLACK));
+}
@@ -0,0 +1,63 @@
+// tests/movegen.rs
+
+use chessr::board::moves::flags;
+use chessr::board::{Move, Position};
+
+fn count(fen: &str) -> usize {
+    Position::from_fen(fen).unwrap().generate_legal_moves().len()
+}
+
+#[test]
+fn root_move_counts() {
+    assert_eq!(Position::startpos().generate_legal_moves().len(), 20);
+    // Kiwipete: castling both ways, en passant-free, many pins
+    assert_eq!(count("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1"), 48);
+    assert_eq!(count("8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1"), 14);
+    assert_eq!(count("r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1"), 6);
+    assert_eq!(count("rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8"), 44);
+}
+
+#[test]
+fn en_passant_discovered_check_is_illegal() {
+    // Capturing en passant would open the fifth rank to the rook on h5
+    let pos = Position::from_fen("8/8/8/K2pP2r/8/8/8/7k w - d6 0 1").unwrap();
+    assert!(pos.generate_legal_moves().iter().all(|mv| !mv.is_en_passant()));
+}
+
+#[test]
+fn castling_through_attack_is_illegal() {
+    // The bishop on c4 covers f1
+    let pos = Position::from_fen("4k3/8/8/8/2b5/8/8/4K2R w K - 0 1").unwrap();
+    assert!(pos.generate_legal_moves().iter().all(|mv| !mv.is_castle()));
+}
+
+#[test]
+fn quiet_moves_checked_on_the_board_match_generation() {
+    let quiet_flags = [flags::QUIET, flags::DOUBLE_PUSH, flags::KING_CASTLE, flags::QUEEN_CASTLE];
+    for fen in [
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R b KQkq - 0 1",
+        "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+        "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+        "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+        // In check from the bishop, with a pinned knight
+        "4k3/8/8/1b6/8/8/4N3/r2K4 w - - 0 1",
+        // Double check
+        "4k3/8/8/8/8/5n2/8/r3K3 w - - 0 1",
+    ] {
+        let pos = Position::from_fen(fen).unwrap();
+        let quiets = pos.generate_quiet_moves();
+        for from in 0..64 {
+            for to in 0..64 {
+                for flag in quiet_flags {
+                    let mv = Move::new(from, to, flag);
+                    assert_eq!(pos.is_legal_quiet(mv), quiets.contains(&mv), "{} {}", fen, mv);
+                }
+            }
+        }
+        for mv in pos.generate_noisy_moves().iter() {
+            assert!(!pos.is_legal_quiet(*mv), "{} {}", fen, mv);
+        }
+    }
+}
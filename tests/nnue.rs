@@ -0,0 +1,118 @@
+// tests/nnue.rs
+#![cfg(feature = "nnue")]
+
+use std::sync::Arc;
+
+use chessr::board::Position;
+use chessr::eval::nnue::network::FEATURES_PER_BUCKET;
+use chessr::eval::nnue::{simd, Network, NetworkError};
+
+const HIDDEN: usize = 32;
+const BUCKETS: usize = 4;
+
+/// Deterministic pseudo-random numbers for weights and move choices
+struct Lcg(u64);
+
+impl Lcg {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+        self.0 >> 33
+    }
+
+    fn weight(&mut self, range: i64) -> i16 {
+        (self.next() as i64 % (2 * range + 1) - range) as i16
+    }
+}
+
+/// A small random network file: four king buckets by board quadrant
+fn network_bytes() -> Vec<u8> {
+    let mut rng = Lcg(7);
+    let mut bytes = b"CRNN".to_vec();
+    for value in [1u32, HIDDEN as u32, BUCKETS as u32, 255, 64, 400] {
+        bytes.extend_from_slice(&value.to_le_bytes());
+    }
+    bytes.extend((0..64).map(|square: usize| ((square / 32) * 2 + (square % 8) / 4) as u8));
+    let weights = BUCKETS * FEATURES_PER_BUCKET * HIDDEN + HIDDEN + 2 * HIDDEN;
+    for _ in 0..weights {
+        bytes.extend_from_slice(&rng.weight(60).to_le_bytes());
+    }
+    bytes.extend_from_slice(&25i32.to_le_bytes());
+    bytes
+}
+
+fn network() -> Arc<Network> {
+    Arc::new(Network::from_bytes(&network_bytes()).unwrap())
+}
+
+/// Evaluation of the same position with freshly built accumulators
+fn fresh_eval(pos: &Position, net: &Arc<Network>) -> i32 {
+    let mut fresh = Position::from_fen(&pos.to_fen()).unwrap();
+    fresh.set_network(Some(Arc::clone(net)));
+    fresh.nnue_evaluate().unwrap()
+}
+
+#[test]
+fn incremental_updates_match_refresh() {
+    let net = network();
+    let mut rng = Lcg(42);
+    for fen in [
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+        "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+    ] {
+        let mut pos = Position::from_fen(fen).unwrap();
+        pos.set_network(Some(Arc::clone(&net)));
+        for ply in 0..120 {
+            let moves = pos.generate_legal_moves();
+            if moves.is_empty() || (ply % 7 == 6 && !pos.undo_stack.is_empty()) {
+                pos.unmake_move();
+            } else {
+                pos.make_move(moves[rng.next() as usize % moves.len()]);
+            }
+            // Skip some evaluations so several deltas are applied at once
+            if !rng.next().is_multiple_of(3) {
+                assert_eq!(pos.nnue_evaluate().unwrap(), fresh_eval(&pos, &net), "{}", pos.to_fen());
+            }
+        }
+    }
+}
+
+#[test]
+fn simd_kernels_match_scalar() {
+    let mut rng = Lcg(3);
+    let acc: Vec<i16> = (0..256).map(|_| rng.weight(600)).collect();
+    let row: Vec<i16> = (0..256).map(|_| rng.weight(600)).collect();
+
+    let (mut fast, mut slow) = (acc.clone(), acc.clone());
+    simd::add(&mut fast, &row);
+    simd::scalar::add(&mut slow, &row);
+    assert_eq!(fast, slow);
+    simd::sub(&mut fast, &row);
+    simd::scalar::sub(&mut slow, &row);
+    assert_eq!(fast, acc);
+    assert_eq!(slow, acc);
+    assert_eq!(simd::crelu_dot(&acc, &row, 255), simd::scalar::crelu_dot(&acc, &row, 255));
+}
+
+#[test]
+fn loader_checks_the_file() {
+    let bytes = network_bytes();
+    let path = std::env::temp_dir().join(format!("chessr-test-{}.nnue", std::process::id()));
+    std::fs::write(&path, &bytes).unwrap();
+    let loaded = Network::load(&path);
+    std::fs::remove_file(&path).unwrap();
+    let net = loaded.unwrap();
+    assert_eq!((net.hidden, net.buckets()), (HIDDEN, BUCKETS));
+
+    let mut bad = bytes.clone();
+    bad[0] = b'X';
+    assert!(matches!(Network::from_bytes(&bad), Err(NetworkError::BadMagic)));
+    assert!(matches!(
+        Network::from_bytes(&bytes[..bytes.len() - 1]),
+        Err(NetworkError::WrongSize { .. })
+    ));
+    let mut bad = bytes.clone();
+    bad[8] = 17; // hidden size not a multiple of 16
+    assert!(matches!(Network::from_bytes(&bad), Err(NetworkError::BadHeader("hidden"))));
+    assert!(Network::load("/nonexistent/net.nnue").is_err());
+}
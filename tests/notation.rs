@@ -0,0 +1,117 @@
+// tests/notation.rs
+
+use chessr::board::{Move, NotationError, Position};
+
+fn pos(fen: &str) -> Position {
+    Position::from_fen(fen).unwrap()
+}
+
+fn san(fen: &str, uci: &str) -> String {
+    let pos = pos(fen);
+    pos.to_san(pos.parse_lan(uci).unwrap())
+}
+
+fn parsed(fen: &str, text: &str) -> String {
+    pos(fen).parse_san(text).map_or_else(|err| err.to_string(), |mv| mv.to_string())
+}
+
+const START: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+const CASTLING: &str = "r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1";
+const PROMOTION: &str = "3k1r2/4P3/8/8/8/8/8/4K3 w - - 0 1";
+
+#[test]
+fn writes_san() {
+    assert_eq!(san(START, "e2e4"), "e4");
+    assert_eq!(san(START, "g1f3"), "Nf3");
+    assert_eq!(san(CASTLING, "e1g1"), "O-O");
+    assert_eq!(san(CASTLING, "e1c1"), "O-O-O");
+    assert_eq!(san("5k2/8/8/8/8/8/8/4K2R w K - 0 1", "e1g1"), "O-O+");
+    assert_eq!(san(PROMOTION, "e7e8q"), "e8=Q+");
+    assert_eq!(san(PROMOTION, "e7e8n"), "e8=N");
+    assert_eq!(san(PROMOTION, "e7f8q"), "exf8=Q+");
+    assert_eq!(san("4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1", "e5d6"), "exd6");
+    assert_eq!(san("7k/8/6K1/8/8/8/8/1Q6 w - - 0 1", "b1b8"), "Qb8#");
+}
+
+#[test]
+fn disambiguates() {
+    let knights = "4k3/8/8/8/8/8/8/1N2KN2 w - - 0 1";
+    assert_eq!(san(knights, "b1d2"), "Nbd2");
+    assert_eq!(san(knights, "f1d2"), "Nfd2");
+    assert_eq!(san(knights, "b1c3"), "Nc3");
+    let rooks = "4k3/8/8/R7/8/8/8/R3K3 w - - 0 1";
+    assert_eq!(san(rooks, "a1a3"), "R1a3");
+    assert_eq!(san(rooks, "a5a3"), "R5a3");
+    let queens = "4k3/8/8/8/8/Q7/8/Q1Q1K3 w - - 0 1";
+    assert_eq!(san(queens, "a1b2"), "Qa1b2");
+    assert_eq!(san(queens, "a3b2"), "Q3b2");
+    assert_eq!(san(queens, "c1b2"), "Qcb2");
+    // A pinned knight is no rival
+    assert_eq!(san("k3r3/8/8/8/8/8/4N3/1N2K3 w - - 0 1", "b1c3"), "Nc3");
+}
+
+#[test]
+fn reads_lenient_san() {
+    assert_eq!(parsed(START, "Nf3"), "g1f3");
+    assert_eq!(parsed(START, "e4!?"), "e2e4");
+    assert_eq!(parsed(CASTLING, "0-0"), "e1g1");
+    assert_eq!(parsed(CASTLING, "O-O-O+"), "e1c1");
+    assert_eq!(parsed(CASTLING, "Rxa8+"), "a1a8");
+    assert_eq!(parsed(PROMOTION, "e8Q"), "e7e8q");
+    assert_eq!(parsed(PROMOTION, "e8=q"), "e7e8q");
+    assert_eq!(parsed(PROMOTION, "exf8=N"), "e7f8n");
+    assert_eq!(parsed(PROMOTION, "ef8Q+"), "e7f8q");
+    assert_eq!(parsed("4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1", "exd6 e.p."), "e5d6");
+    assert_eq!(parsed("4k3/8/8/8/8/8/8/1N2KN2 w - - 0 1", "Nb1d2"), "b1d2");
+    // LAN is understood too
+    assert_eq!(parsed(START, "g1f3"), "g1f3");
+    assert_eq!(parsed(CASTLING, "e1g1"), "e1g1");
+}
+
+#[test]
+fn rejects_bad_san() {
+    let knights = pos("4k3/8/8/8/8/8/8/1N2KN2 w - - 0 1");
+    assert_eq!(knights.parse_san("Nd2"), Err(NotationError::Ambiguous("Nd2".to_string())));
+    assert_eq!(knights.parse_san("Nd3"), Err(NotationError::Illegal("Nd3".to_string())));
+    assert_eq!(knights.parse_san("Zz9"), Err(NotationError::Malformed("Zz9".to_string())));
+    assert!(matches!(knights.parse_san(""), Err(NotationError::Malformed(_))));
+    assert!(matches!(knights.parse_san("e8=K"), Err(NotationError::Malformed(_))));
+    assert!(matches!(pos(START).parse_san("O-O"), Err(NotationError::Illegal(_))));
+    // A promotion has to name its piece
+    assert!(matches!(pos(PROMOTION).parse_san("e8"), Err(NotationError::Illegal(_))));
+}
+
+#[test]
+fn reads_lan() {
+    let start = pos(START);
+    for text in ["e2e4", "E2E4", "e2-e4", "Pe2-e4"] {
+        assert_eq!(start.parse_lan(text).map(|mv| mv.to_string()), Ok("e2e4".to_string()));
+    }
+    for text in ["b2b4", "B2B4", "Pb2b4"] {
+        assert_eq!(start.parse_lan(text).map(|mv| mv.to_string()), Ok("b2b4".to_string()));
+    }
+    assert_eq!(start.parse_lan("Ng1-f3").unwrap().to_string(), "g1f3");
+    assert_eq!(pos(PROMOTION).parse_lan("e7xf8=Q").unwrap().to_string(), "e7f8q");
+    assert!(matches!(start.parse_lan("e2e5"), Err(NotationError::Illegal(_))));
+    assert!(matches!(start.parse_lan("e2"), Err(NotationError::Malformed(_))));
+}
+
+#[test]
+fn round_trips() {
+    for fen in [
+        START,
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+        "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+        "4k3/8/8/8/8/Q7/8/Q1Q1K3 w - - 0 1",
+    ] {
+        let pos = pos(fen);
+        let moves = pos.generate_legal_moves();
+        let sans: Vec<String> = moves.iter().map(|&mv| pos.to_san(mv)).collect();
+        for (&mv, san) in moves.iter().zip(&sans) {
+            assert_eq!(pos.parse_san(san), Ok(mv), "{} in {}", san, fen);
+            assert_eq!(pos.parse_lan(&mv.to_string()), Ok::<Move, NotationError>(mv));
+            assert_eq!(sans.iter().filter(|other| *other == san).count(), 1, "{}", san);
+        }
+    }
+}
@@ -0,0 +1,75 @@
+// tests/perft.rs
+
+//! Standard perft suite (https://www.chessprogramming.org/Perft_Results).
+//! The deeper counts are ignored by default; run them with
+//! `cargo test --release --test perft -- --ignored`.
+
+use chessr::board::fen::STARTPOS_FEN;
+use chessr::board::Position;
+
+const KIWIPETE: &str = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1";
+const POSITION_3: &str = "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1";
+const POSITION_4: &str = "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1";
+const POSITION_4_MIRRORED: &str =
+    "r2q1rk1/pP1p2pp/Q4n2/bbp1p3/Np6/1B3NBn/pPPP1PPP/R3K2R b KQ - 0 1";
+const POSITION_5: &str = "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8";
+const POSITION_6: &str =
+    "r4rk1/1pp1qppp/p1np1n2/2b1p1B1/2B1P1b1/P1NP1N2/1PP1QPPP/R4RK1 w - - 0 10";
+
+fn check(fen: &str, expected: &[u64]) {
+    let mut pos = Position::from_fen(fen).unwrap();
+    for (depth, &nodes) in expected.iter().enumerate() {
+        assert_eq!(pos.perft(depth as u32 + 1), nodes, "{} depth {}", fen, depth + 1);
+    }
+    assert_eq!(pos.to_fen(), Position::from_fen(fen).unwrap().to_fen());
+}
+
+#[test]
+fn perft_initial() {
+    check(STARTPOS_FEN, &[20, 400, 8902, 197281]);
+}
+
+#[test]
+fn perft_kiwipete() {
+    check(KIWIPETE, &[48, 2039, 97862]);
+}
+
+#[test]
+fn perft_position_3() {
+    check(POSITION_3, &[14, 191, 2812, 43238, 674624]);
+}
+
+#[test]
+fn perft_position_4() {
+    check(POSITION_4, &[6, 264, 9467, 422333]);
+    check(POSITION_4_MIRRORED, &[6, 264, 9467, 422333]);
+}
+
+#[test]
+fn perft_position_5() {
+    check(POSITION_5, &[44, 1486, 62379]);
+}
+
+#[test]
+fn perft_position_6() {
+    check(POSITION_6, &[46, 2079, 89890]);
+}
+
+#[test]
+fn divide_sums_to_perft() {
+    let mut pos = Position::from_fen(KIWIPETE).unwrap();
+    let counts = pos.divide(3);
+    assert_eq!(counts.len(), 48);
+    assert_eq!(counts.iter().map(|&(_, n)| n).sum::<u64>(), 97862);
+}
+
+#[test]
+#[ignore]
+fn perft_deep() {
+    check(STARTPOS_FEN, &[20, 400, 8902, 197281, 4865609, 119060324]);
+    check(KIWIPETE, &[48, 2039, 97862, 4085603, 193690690]);
+    check(POSITION_3, &[14, 191, 2812, 43238, 674624, 11030083]);
+    check(POSITION_4, &[6, 264, 9467, 422333, 15833292]);
+    check(POSITION_5, &[44, 1486, 62379, 2103487, 89941194]);
+    check(POSITION_6, &[46, 2079, 89890, 3894594, 164075551]);
+}
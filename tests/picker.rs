@@ -0,0 +1,58 @@
+// tests/picker.rs
+
+use chessr::board::{Move, Position};
+use chessr::search::{History, MovePicker};
+
+fn find(pos: &Position, uci: &str) -> Move {
+    *pos.generate_legal_moves().iter().find(|mv| mv.to_string() == uci).unwrap()
+}
+
+fn picked(pos: &Position, hash_move: Move, killers: [Move; 2], countermove: Move) -> Vec<Move> {
+    let history = History::new();
+    let mut picker = MovePicker::new(hash_move, killers, countermove, [None; 2]);
+    let mut moves = Vec::new();
+    while let Some(mv) = picker.next(pos, &history) {
+        moves.push(mv);
+    }
+    moves
+}
+
+#[test]
+fn picks_every_legal_move_once() {
+    let other = Position::from_fen("4k3/8/8/8/8/8/8/R3K2R w KQ - 0 1").unwrap();
+    let foreign = [find(&other, "e1g1"), find(&other, "a1a8"), find(&other, "h1h5")];
+    for fen in [
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+        "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+        "4k3/8/8/8/8/8/4q3/4K3 w - - 0 1",
+    ] {
+        let pos = Position::from_fen(fen).unwrap();
+        let mut legal: Vec<Move> = pos.generate_legal_moves().to_vec();
+        legal.sort_by_key(|mv| mv.0);
+        // Moves from another position must not be played here
+        let specials = [legal[0], legal[legal.len() / 2], *legal.last().unwrap()];
+        for hash_move in [Move::NONE, specials[0], foreign[0]] {
+            for killers in [[specials[1], foreign[1]], [foreign[2], specials[2]]] {
+                let mut moves = picked(&pos, hash_move, killers, foreign[0]);
+                moves.sort_by_key(|mv| mv.0);
+                assert_eq!(moves, legal, "{}", fen);
+            }
+        }
+    }
+}
+
+#[test]
+fn stages_come_in_order() {
+    // Nxd5 wins a pawn, Rxa5 loses the rook to bxa5
+    let pos = Position::from_fen("4k3/8/1p6/p2p4/8/2N5/8/R3K3 w - - 0 1").unwrap();
+    let hash_move = find(&pos, "e1d2");
+    let killers = [find(&pos, "c3b5"), find(&pos, "a1a2")];
+    let countermove = find(&pos, "c3e4");
+    let moves: Vec<String> =
+        picked(&pos, hash_move, killers, countermove).iter().map(|mv| mv.to_string()).collect();
+    assert_eq!(moves[..5], ["e1d2", "c3d5", "c3b5", "a1a2", "c3e4"]);
+    assert_eq!(moves.last().unwrap(), "a1a5");
+    assert_eq!(moves.len(), pos.generate_legal_moves().len());
+}
@@ -0,0 +1,91 @@
+// tests/search.rs
+
+use chessr::board::Position;
+use chessr::eval::PawnTable;
+use chessr::search::{think, SearchLimits, SearchParams, SearchSignals, TranspositionTable};
+
+fn best_move(fen: &str, limits: SearchLimits) -> String {
+    let pos = Position::from_fen(fen).unwrap();
+    let tt = TranspositionTable::new(1);
+    let mut pawns = [PawnTable::new()];
+    think(&pos, &limits, &SearchSignals::default(), &tt, &mut pawns, None).best_move.to_string()
+}
+
+#[test]
+fn finds_mate_in_one() {
+    let limits = SearchLimits { depth: Some(3), ..Default::default() };
+    assert_eq!(
+        best_move("r1bqkbnr/pppp1ppp/2n5/4p2Q/2B1P3/8/PPPP1PPP/RNB1K1NR w KQkq - 2 3", limits),
+        "h5f7"
+    );
+}
+
+#[test]
+fn wins_hanging_queen() {
+    let limits = SearchLimits { depth: Some(2), ..Default::default() };
+    assert_eq!(best_move("4k3/8/8/3q4/8/8/3R4/4K3 w - - 0 1", limits), "d2d5");
+}
+
+#[test]
+fn respects_searchmoves_and_node_limit() {
+    let pos = Position::startpos();
+    let a2a3 = *pos.generate_legal_moves().iter().find(|mv| mv.to_string() == "a2a3").unwrap();
+    let limits = SearchLimits { nodes: Some(5000), searchmoves: vec![a2a3], ..Default::default() };
+    let tt = TranspositionTable::new(1);
+    let mut pawns = [PawnTable::new()];
+    let result = think(&pos, &limits, &SearchSignals::default(), &tt, &mut pawns, None);
+    assert_eq!(result.best_move, a2a3);
+}
+
+#[test]
+fn stopped_before_any_root_move_still_moves() {
+    let pos = Position::startpos();
+    let tt = TranspositionTable::new(1);
+    let mut pawns = [PawnTable::new()];
+    for nodes in [1, 2, 20] {
+        let limits = SearchLimits { nodes: Some(nodes), ..Default::default() };
+        let best = think(&pos, &limits, &SearchSignals::default(), &tt, &mut pawns, None).best_move;
+        assert!(pos.generate_legal_moves().contains(&best), "nodes {}", nodes);
+    }
+}
+
+#[test]
+fn lazy_smp_finds_mate_in_one() {
+    let pos =
+        Position::from_fen("r1bqkbnr/pppp1ppp/2n5/4p2Q/2B1P3/8/PPPP1PPP/RNB1K1NR w KQkq - 2 3")
+            .unwrap();
+    let limits = SearchLimits { depth: Some(4), ..Default::default() };
+    let tt = TranspositionTable::new(1);
+    let mut pawns: Vec<PawnTable> = (0..4).map(|_| PawnTable::new()).collect();
+    let result = think(&pos, &limits, &SearchSignals::default(), &tt, &mut pawns, None);
+    assert_eq!(result.best_move.to_string(), "h5f7");
+}
+
+#[test]
+fn every_pruning_switch_keeps_simple_tactics() {
+    let none = SearchParams::NONE;
+    let mut variants = vec![none, SearchParams::ALL];
+    variants.push(SearchParams { null_move: true, ..none });
+    variants.push(SearchParams { late_move_reductions: true, ..none });
+    variants.push(SearchParams { reverse_futility: true, ..none });
+    variants.push(SearchParams { futility: true, ..none });
+    variants.push(SearchParams { razoring: true, ..none });
+    variants.push(SearchParams { late_move_pruning: true, ..none });
+    variants.push(SearchParams { check_extensions: true, ..none });
+    for params in variants {
+        let limits = SearchLimits { depth: Some(4), params, ..Default::default() };
+        let fen = "r1bqkbnr/pppp1ppp/2n5/4p2Q/2B1P3/8/PPPP1PPP/RNB1K1NR w KQkq - 2 3";
+        assert_eq!(best_move(fen, limits.clone()), "h5f7", "{:?}", params);
+        assert_eq!(best_move("4k3/8/8/3q4/8/8/3R4/4K3 w - - 0 1", limits), "d2d5", "{:?}", params);
+    }
+}
+
+#[test]
+fn finds_mate_in_two_with_and_without_pruning() {
+    // 1. Nf6+ gxf6 2. Bxf7#
+    let fen = "r2qkb1r/pp2nppp/3p4/2pNN1B1/2BnP3/3P4/PPP2PPP/R2bK2R w KQkq - 1 10";
+    for params in [SearchParams::NONE, SearchParams::ALL] {
+        let limits = SearchLimits { depth: Some(5), params, ..Default::default() };
+        assert_eq!(best_move(fen, limits), "d5f6", "{:?}", params);
+    }
+}
@@ -0,0 +1,36 @@
+// tests/see.rs
+
+use chessr::board::Position;
+
+fn see(fen: &str, uci: &str) -> i32 {
+    let pos = Position::from_fen(fen).unwrap();
+    let mv = *pos.generate_legal_moves().iter().find(|mv| mv.to_string() == uci).unwrap();
+    pos.see(mv)
+}
+
+#[test]
+fn see_simple_exchanges() {
+    // Undefended pawn
+    assert_eq!(see("1k1r4/1pp4p/p7/4p3/8/P5P1/1PP4P/2K1R3 w - - 0 1", "e1e5"), 100);
+    // Pawn defended by a pawn: rook for pawn
+    assert_eq!(see("4k3/8/5p2/4p3/8/8/8/K3R3 w - - 0 1", "e1e5"), 100 - 500);
+    // Knight takes a pawn defended by a knight, behind which a queen x-rays through
+    assert_eq!(
+        see("1k1r3q/1ppn3p/p4b2/4p3/8/P2N2P1/1PP1R1BP/2K1Q3 w - - 0 1", "d3e5"),
+        100 - 320
+    );
+}
+
+#[test]
+fn see_counts_xray_recaptures() {
+    // Doubled rooks win the pawn even though it is defended once
+    assert_eq!(see("4k3/4r3/8/4p3/8/8/4R3/4R1K1 w - - 0 1", "e2e5"), 100);
+    // Without the second rook the defender wins the exchange
+    assert_eq!(see("4k3/4r3/8/4p3/8/8/4R3/6K1 w - - 0 1", "e2e5"), 100 - 500);
+}
+
+#[test]
+fn see_king_cannot_recapture_defended_piece() {
+    // Queen takes a pawn next to the enemy king, protected by a rook
+    assert_eq!(see("6k1/5p2/8/8/8/8/5Q2/5RK1 w - - 0 1", "f2f7"), 100);
+}
@@ -0,0 +1,86 @@
+// tests/status.rs
+
+use chessr::board::{GameStatus, Position};
+
+fn play(pos: &mut Position, moves: &[&str]) {
+    for uci in moves {
+        let mv = *pos.generate_legal_moves().iter().find(|mv| mv.to_string() == *uci).unwrap();
+        pos.make_move(mv);
+    }
+}
+
+fn status(fen: &str) -> GameStatus {
+    Position::from_fen(fen).unwrap().status()
+}
+
+#[test]
+fn mate_stalemate_and_fifty_moves() {
+    assert_eq!(status("Q6k/8/6K1/8/8/8/8/8 b - - 0 80"), GameStatus::Checkmate);
+    // Mate on the hundredth halfmove is still mate
+    assert_eq!(status("Q6k/8/6K1/8/8/8/8/8 b - - 100 80"), GameStatus::Checkmate);
+    assert_eq!(status("7k/5Q2/6K1/8/8/8/8/8 b - - 0 80"), GameStatus::Stalemate);
+    assert_eq!(status("8/8/8/8/8/2k5/8/K6R b - - 99 80"), GameStatus::Ongoing);
+    assert_eq!(status("8/8/8/8/8/2k5/8/K6R b - - 100 80"), GameStatus::FiftyMoveRule);
+    assert!(GameStatus::FiftyMoveRule.is_draw());
+    assert!(!GameStatus::Checkmate.is_draw());
+}
+
+#[test]
+fn threefold_repetition() {
+    let mut pos = Position::startpos();
+    let shuffle = ["g1f3", "g8f6", "f3g1", "f6g8"];
+    play(&mut pos, &shuffle);
+    assert_eq!(pos.repetitions(), 2);
+    assert_eq!(pos.status(), GameStatus::Ongoing);
+    play(&mut pos, &shuffle);
+    assert_eq!(pos.repetitions(), 3);
+    assert_eq!(pos.status(), GameStatus::ThreefoldRepetition);
+
+    // A pawn move makes every earlier position unreachable
+    play(&mut pos, &["e2e4", "e7e5"]);
+    play(&mut pos, &shuffle);
+    assert_eq!(pos.repetitions(), 2);
+}
+
+#[test]
+fn repetitions_in_search() {
+    let mut pos = Position::startpos();
+    play(&mut pos, &["g1f3", "g8f6", "f3g1", "f6g8"]);
+    // Once inside the search is enough; once before the root is not
+    assert!(pos.is_repetition_draw(4));
+    assert!(!pos.is_repetition_draw(3));
+    play(&mut pos, &["b1c3", "b8c6", "c3b1", "c6b8"]);
+    assert!(pos.is_repetition_draw(0));
+
+    // Passing is not a move of the game
+    let mut pos = Position::startpos();
+    play(&mut pos, &["g1f3"]);
+    pos.make_null_move();
+    play(&mut pos, &["f3g1"]);
+    pos.make_null_move();
+    assert_eq!(pos.hash, Position::startpos().hash);
+    assert!(!pos.is_repetition_draw(8));
+}
+
+#[test]
+fn insufficient_material() {
+    for fen in [
+        "4k3/8/8/8/8/8/8/4K3 w - - 0 1",
+        "4k3/8/8/8/8/8/8/2B1K3 w - - 0 1",
+        "4k3/8/8/8/8/8/8/4K1n1 b - - 0 1",
+        // Bishops on dark squares only
+        "4k3/8/8/8/8/2b5/8/2B1K3 w - - 0 1",
+        "4k3/8/8/8/8/8/3B4/2B1K3 w - - 0 1",
+    ] {
+        assert_eq!(status(fen), GameStatus::InsufficientMaterial, "{}", fen);
+    }
+    for fen in [
+        "4k3/8/8/8/8/8/8/1bB1K3 w - - 0 1",
+        "4k3/8/8/8/8/8/8/1nN1K3 w - - 0 1",
+        "4k3/8/8/8/8/8/8/1nB1K3 w - - 0 1",
+        "4k3/8/8/8/8/8/4P3/4K3 w - - 0 1",
+        "4k3/8/8/8/8/8/8/R3K3 w - - 0 1",
+    ] {
+        assert_eq!(status(fen), GameStatus::Ongoing, "{}", fen);
+    }
+}
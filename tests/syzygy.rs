@@ -0,0 +1,180 @@
+// tests/syzygy.rs
+
+use std::env;
+use std::fs;
+use std::path::Path;
+
+use chessr::board::Position;
+use chessr::eval::PawnTable;
+use chessr::search::{think, SearchLimits, SearchSignals, TranspositionTable};
+use chessr::syzygy::index::indexing;
+use chessr::syzygy::table::Material;
+use chessr::syzygy::{piece_count, Tablebases, Wdl};
+
+/// Tables the probing tests need, in `tests/syzygy` or a directory of `SYZYGY_PATH`.
+/// They are not in the repository yet, so those tests are ignored by default. Fetch the
+/// `.rtbw` and `.rtbz` files from https://tablebase.lichess.ovh/tables/standard/3-4-5/
+/// and run them with `cargo test --test syzygy -- --ignored`.
+const NEEDED: [&str; 5] = ["KQvK", "KRvK", "KPvK", "KQvKN", "KRvKR"];
+
+/// Tables from `SYZYGY_PATH`, or else the `tests/syzygy` directory. Fails the test when
+/// any of the needed tables is missing.
+fn tablebases() -> Tablebases {
+    let path = env::var("SYZYGY_PATH")
+        .unwrap_or_else(|_| concat!(env!("CARGO_MANIFEST_DIR"), "/tests/syzygy").to_string());
+    for name in NEEDED {
+        let found = env::split_paths(&path).any(|dir| {
+            let file = |ext: &str| dir.join(format!("{}.{}", name, ext)).is_file();
+            file("rtbw") && file("rtbz")
+        });
+        assert!(found, "{}.rtbw and {}.rtbz not found in {}", name, name, path);
+    }
+    let tb = Tablebases::open(&path);
+    assert!(tb.max_pieces() >= 4);
+    tb
+}
+
+fn wdl(tb: &Tablebases, fen: &str) -> Option<Wdl> {
+    tb.probe_wdl(&mut Position::from_fen(fen).unwrap())
+}
+
+#[test]
+fn index_tables() {
+    let ix = indexing();
+
+    // 462 placements of two kings up to symmetry, each with its own code
+    let mut codes: Vec<usize> = Vec::new();
+    for first in [1, 2, 3, 10, 11, 19, 0, 9, 18, 27] {
+        let idx = ix.map_a1d1d4[first];
+        for second in 0..64 {
+            let apart = (first % 8).abs_diff(second % 8) > 1 || (first / 8).abs_diff(second / 8) > 1;
+            let above = |sq: usize| sq / 8 > sq % 8;
+            let on = |sq: usize| sq / 8 == sq % 8;
+            if apart && !(on(first) && above(second)) {
+                codes.push(ix.map_kk[idx][second]);
+            }
+        }
+    }
+    codes.sort_unstable();
+    assert_eq!(codes, (0..462).collect::<Vec<_>>());
+
+    // Pawn squares a2-h7 get distinct codes, the a2 pawn leading
+    let mut pawns: Vec<usize> = (8..56).map(|sq| ix.map_pawns[sq]).collect();
+    pawns.sort_unstable();
+    assert_eq!(pawns, (0..48).collect::<Vec<_>>());
+    assert_eq!(ix.map_pawns[8], 47);
+
+    assert_eq!(ix.binomial[2][5], 10);
+    assert_eq!(ix.binomial[5][62], 6_471_002);
+    assert_eq!(ix.lead_pawns_size[1], [6, 6, 6, 6]);
+}
+
+#[test]
+fn table_names() {
+    let m = Material::from_name("KRPvKR").unwrap();
+    assert_eq!((m.piece_count, m.has_pawns, m.has_unique_pieces), (5, true, true));
+    assert_ne!(m.key, m.key2);
+    assert_eq!(Material::from_name("KRvKR").map(|m| m.key == m.key2), Some(true));
+    // The side with fewer pawns leads, unless it has none
+    assert_eq!(Material::from_name("KPPvKP").unwrap().pawn_count, [1, 2]);
+    assert_eq!(Material::from_name("KPvK").unwrap().pawn_count, [1, 0]);
+    assert!(Material::from_name("KQvQ").is_none());
+    assert!(Material::from_name("KQ").is_none());
+    assert!(Material::from_name("KQQQQvKQQ").is_none());
+}
+
+#[test]
+fn missing_and_broken_tables_are_not_probed() {
+    let tb = Tablebases::open("/nonexistent");
+    assert!(tb.is_empty());
+    assert_eq!(piece_count(&Position::startpos()), 32);
+
+    let dir = env::temp_dir().join(format!("chessr-syzygy-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(dir.join("KQvK.rtbw"), b"not a table").unwrap();
+    fs::write(dir.join("notes.txt"), b"").unwrap();
+    fs::write(dir.join("KQQQQQQvK.rtbw"), b"").unwrap();
+    let tb = Tablebases::open(dir.to_str().unwrap());
+    assert_eq!((tb.len(), tb.max_pieces()), (1, 3));
+    assert_eq!(wdl(&tb, "4k3/8/8/8/8/8/8/3QK3 w - - 0 1"), None);
+    // Bare kings need no table
+    assert_eq!(wdl(&tb, "4k3/8/8/8/8/8/8/4K3 w - - 0 1"), Some(Wdl::Draw));
+    // Too many pieces, or castling rights
+    assert_eq!(wdl(&tb, "4k3/8/8/8/8/8/8/R2QK3 w - - 0 1"), None);
+    assert_eq!(wdl(&tb, "4k3/8/8/8/8/8/8/4K2R w K - 0 1"), None);
+    fs::remove_dir_all(&dir).unwrap();
+    assert!(!Path::new(&dir).exists());
+}
+
+#[test]
+#[ignore = "needs Syzygy tables, see NEEDED"]
+fn probes_wdl() {
+    let tb = tablebases();
+    assert_eq!(wdl(&tb, "4k3/8/8/8/8/8/8/4KQ2 w - - 0 1"), Some(Wdl::Win));
+    assert_eq!(wdl(&tb, "4k3/8/8/8/8/8/8/4KQ2 b - - 0 1"), Some(Wdl::Loss));
+    // Black to move takes the queen
+    assert_eq!(wdl(&tb, "8/8/8/8/8/8/5kQ1/2K5 b - - 0 1"), Some(Wdl::Draw));
+    assert_eq!(wdl(&tb, "4k3/8/8/8/8/8/8/R3K3 b - - 0 1"), Some(Wdl::Loss));
+    assert_eq!(wdl(&tb, "8/8/8/8/8/8/4P3/4K2k w - - 0 1"), Some(Wdl::Win));
+    assert_eq!(wdl(&tb, "k7/8/8/8/8/8/P7/K7 w - - 0 1"), Some(Wdl::Draw));
+    // Stalemate
+    assert_eq!(wdl(&tb, "4k3/4P3/4K3/8/8/8/8/8 b - - 0 1"), Some(Wdl::Draw));
+    assert_eq!(wdl(&tb, "4k3/8/8/3n4/8/8/8/3QK3 w - - 0 1"), Some(Wdl::Win));
+    assert_eq!(wdl(&tb, "4k3/8/8/3n4/8/8/8/3QK3 b - - 0 1"), Some(Wdl::Loss));
+    // Both colourings of a symmetric table
+    assert_eq!(wdl(&tb, "3k4/8/8/8/8/8/r7/4K2R w - - 0 1"), Some(Wdl::Draw));
+    assert_eq!(wdl(&tb, "3k4/8/8/8/8/8/r7/4K2R b - - 0 1"), Some(Wdl::Draw));
+}
+
+#[test]
+#[ignore = "needs Syzygy tables, see NEEDED"]
+fn probes_dtz_and_ranks_root_moves() {
+    let tb = tablebases();
+    let dtz = |fen: &str| tb.probe_dtz(&mut Position::from_fen(fen).unwrap());
+    // Mate in one, and wins where a pawn move or capture (which zeroes) keeps the win
+    assert_eq!(dtz("7k/8/6K1/8/8/8/8/1Q6 w - - 0 1"), Some(1));
+    assert_eq!(dtz("8/8/8/8/8/8/4P3/4K2k w - - 0 1"), Some(1));
+    assert_eq!(dtz("3k4/8/8/8/8/8/r7/R3K3 w - - 0 1"), Some(1));
+    assert_eq!(dtz("k7/8/8/8/8/8/P7/K7 w - - 0 1"), Some(0));
+
+    let pos = Position::from_fen("7k/8/6K1/8/8/8/8/1Q6 w - - 0 1").unwrap();
+    let ranking = tb.rank_root_moves(&pos, &[]).unwrap();
+    assert!(ranking.dtz && ranking.winning);
+    let moves: Vec<String> = ranking.moves.iter().map(|mv| mv.to_string()).collect();
+    assert_eq!(moves, ["b1b8"]);
+
+    let mut pos = Position::from_fen("4k3/8/8/8/8/8/8/4KQ2 b - - 0 1").unwrap();
+    assert!(tb.probe_dtz(&mut pos).unwrap() < 0);
+
+    // Late enough in a KRvK ending the rook can no longer mate before the fifty-move rule.
+    // The win is only cursed then, which still counts as winning, and the quickest moves
+    // are kept all the same.
+    let far = "8/8/8/8/8/2k5/8/K6R w - - 0 1";
+    let mut pos = Position::from_fen(far).unwrap();
+    let dtz = tb.probe_dtz(&mut pos).unwrap();
+    assert!(dtz > 0);
+    let early = tb.rank_root_moves(&pos, &[]).unwrap();
+    let late = far.replace("- 0 1", &format!("- {} 60", 101 - dtz));
+    let ranking = tb.rank_root_moves(&Position::from_fen(&late).unwrap(), &[]).unwrap();
+    assert!(early.winning && ranking.winning);
+    assert_eq!(ranking.moves, early.moves);
+
+    // A drawn root is not
+    let pos = Position::from_fen("3k4/8/8/8/8/8/r7/4K2R w - - 0 1").unwrap();
+    assert!(!tb.rank_root_moves(&pos, &[]).unwrap().winning);
+}
+
+#[test]
+#[ignore = "needs Syzygy tables, see NEEDED"]
+fn search_keeps_the_win() {
+    let tb = tablebases();
+    let pos = Position::from_fen("8/8/3k4/8/8/8/8/3K3R w - - 0 1").unwrap();
+    let limits = SearchLimits { depth: Some(4), ..Default::default() };
+    let tt = TranspositionTable::new(1);
+    let mut pawns = [PawnTable::new()];
+    let signals = SearchSignals::default();
+    let best = think(&pos, &limits, &signals, &tt, &mut pawns, Some(&tb)).best_move;
+    let mut after = pos.clone();
+    after.make_move(best);
+    assert_eq!(tb.probe_wdl(&mut after), Some(Wdl::Loss));
+}
@@ -0,0 +1,107 @@
+// tests/time.rs
+
+use std::time::{Duration, Instant};
+
+use chessr::board::{sides, Position};
+use chessr::eval::PawnTable;
+use chessr::search::{think, SearchLimits, SearchSignals, TimeManager, TranspositionTable, MATE};
+
+fn ms(millis: u64) -> Option<Duration> {
+    Some(Duration::from_millis(millis))
+}
+
+fn deadlines(limits: SearchLimits, side: usize) -> (Option<Duration>, Option<Duration>) {
+    let time = TimeManager::new(&limits, side);
+    (time.soft(), time.hard())
+}
+
+#[test]
+fn fixed_move_time() {
+    let limits = SearchLimits { movetime: Some(1000), move_overhead: 50, ..Default::default() };
+    assert_eq!(deadlines(limits, sides::BLACK), (ms(950), ms(950)));
+}
+
+#[test]
+fn clock_deadlines() {
+    let sudden_death =
+        SearchLimits { wtime: Some(60_000), btime: Some(1000), ..Default::default() };
+    assert_eq!(deadlines(sudden_death.clone(), sides::WHITE), (ms(2000), ms(8000)));
+    assert_eq!(deadlines(sudden_death, sides::BLACK), (ms(33), ms(132)));
+
+    let increment = SearchLimits { wtime: Some(10_000), winc: Some(1000), ..Default::default() };
+    assert_eq!(deadlines(increment, sides::WHITE), (ms(1083), ms(4332)));
+
+    let last_move = SearchLimits { btime: Some(1000), movestogo: Some(1), ..Default::default() };
+    assert_eq!(deadlines(last_move, sides::BLACK), (ms(500), ms(750)));
+
+    // A large increment cannot make up for an almost empty clock
+    let low =
+        SearchLimits { wtime: Some(110), winc: Some(5000), move_overhead: 10, ..Default::default() };
+    assert_eq!(deadlines(low, sides::WHITE), (ms(50), ms(75)));
+}
+
+#[test]
+fn unlimited_searches() {
+    let only_black = SearchLimits { btime: Some(1000), ..Default::default() };
+    assert_eq!(deadlines(only_black, sides::WHITE), (None, None));
+    let infinite = SearchLimits { wtime: Some(1000), infinite: true, ..Default::default() };
+    assert_eq!(deadlines(infinite, sides::WHITE), (None, None));
+    let depth = SearchLimits { depth: Some(5), ..Default::default() };
+    assert!(!TimeManager::new(&depth, sides::WHITE).is_limited());
+}
+
+#[test]
+fn stretches_soft_deadline() {
+    let moves = Position::startpos().generate_legal_moves();
+    let limits = SearchLimits { wtime: Some(60_000), ..Default::default() };
+
+    let mut time = TimeManager::new(&limits, sides::WHITE);
+    time.update(moves[0], 20);
+    time.update(moves[0], 25);
+    assert_eq!(time.soft(), ms(2000));
+    time.update(moves[1], 25);
+    assert_eq!(time.soft(), ms(4000));
+    time.update(moves[1], 25);
+    assert_eq!(time.soft(), ms(3000));
+    // At most threefold, and never past the hard deadline
+    for i in 0..10 {
+        time.update(moves[i % 2], -200 * i as i32);
+    }
+    assert_eq!(time.soft(), ms(6000));
+    let last_move = SearchLimits { wtime: Some(1000), movestogo: Some(1), ..Default::default() };
+    let mut time = TimeManager::new(&last_move, sides::WHITE);
+    time.update(moves[0], 0);
+    time.update(moves[1], 0);
+    assert_eq!(time.soft(), time.hard());
+
+    let mut time = TimeManager::new(&limits, sides::WHITE);
+    time.update(moves[0], 50);
+    time.update(moves[0], -50);
+    assert_eq!(time.soft(), ms(3000));
+    time.update(moves[0], -MATE + 10);
+    assert_eq!(time.soft(), ms(2000));
+}
+
+#[test]
+fn single_legal_move_is_played_at_once() {
+    let pos = Position::from_fen("7k/8/5QK1/8/8/8/8/8 b - - 0 1").unwrap();
+    assert_eq!(pos.generate_legal_moves().len(), 1);
+    let limits = SearchLimits { btime: Some(600_000), ..Default::default() };
+    let tt = TranspositionTable::new(1);
+    let mut pawns = [PawnTable::new()];
+    let start = Instant::now();
+    let result = think(&pos, &limits, &SearchSignals::default(), &tt, &mut pawns, None);
+    assert_eq!(result.best_move.to_string(), "h8g8");
+    assert!(start.elapsed() < Duration::from_secs(5));
+}
+
+#[test]
+fn ponderhit_restarts_the_deadlines() {
+    let limits = SearchLimits { movetime: Some(50), ..Default::default() };
+    let mut time = TimeManager::new(&limits, sides::WHITE);
+    std::thread::sleep(Duration::from_millis(60));
+    assert!(time.hard_limit_reached());
+    time.ponderhit();
+    assert!(!time.soft_limit_reached() && !time.hard_limit_reached());
+    assert!(time.elapsed() >= Duration::from_millis(60));
+}
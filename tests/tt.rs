@@ -0,0 +1,39 @@
+// tests/tt.rs
+
+use chessr::board::Position;
+use chessr::search::tt::{Bound, TranspositionTable};
+use chessr::search::MATE;
+
+#[test]
+fn store_and_probe() {
+    let tt = TranspositionTable::new(1);
+    let pos = Position::startpos();
+    let mv = pos.generate_legal_moves()[0];
+
+    assert!(tt.probe(pos.hash, 0).is_none());
+    tt.store(pos.hash, mv, 37, 5, Bound::Lower, 0);
+    let entry = tt.probe(pos.hash, 0).unwrap();
+    assert_eq!((entry.best_move, entry.score, entry.depth, entry.bound), (mv, 37, 5, Bound::Lower));
+
+    tt.clear();
+    assert!(tt.probe(pos.hash, 0).is_none());
+}
+
+#[test]
+fn mate_scores_are_relative_to_the_node() {
+    let tt = TranspositionTable::new(1);
+    // Mate found 3 plies below a node at ply 4 (mate in 7 plies from the root)
+    tt.store(42, Default::default(), MATE - 7, 3, Bound::Exact, 4);
+    // Reached again at ply 2, the mate is 5 plies away from the root
+    assert_eq!(tt.probe(42, 2).unwrap().score, MATE - 5);
+}
+
+#[test]
+fn shallow_result_keeps_best_move() {
+    let tt = TranspositionTable::new(1);
+    let pos = Position::startpos();
+    let mv = pos.generate_legal_moves()[3];
+    tt.store(pos.hash, mv, 10, 4, Bound::Exact, 0);
+    tt.store(pos.hash, Default::default(), -5, 5, Bound::Upper, 0);
+    assert_eq!(tt.probe(pos.hash, 0).unwrap().best_move, mv);
+}
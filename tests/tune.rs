@@ -0,0 +1,58 @@
+// tests/tune.rs
+
+use chessr::board::{sides, Position};
+use chessr::eval::{evaluate, DEFAULT_PARAMS};
+use chessr::tune::{parse_line, unit_params, Sample, Tuner};
+
+const FENS: [&str; 4] = [
+    "r1bq1rk1/pp2bppp/2n1pn2/3p4/2PP4/2N1PN2/PP3PPP/R1BQKB1R w KQ - 0 8",
+    "8/5pk1/6p1/3P4/1p6/1P4P1/5PK1/8 b - - 0 40",
+    "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+    "4k3/8/8/8/8/8/8/R3K3 w - - 0 1",
+];
+
+#[test]
+fn trace_reproduces_the_evaluation() {
+    let weights: Vec<[f64; 2]> =
+        DEFAULT_PARAMS.to_vec().iter().map(|s| [s.mg as f64, s.eg as f64]).collect();
+    let units = unit_params();
+    for fen in FENS {
+        let pos = Position::from_fen(fen).unwrap();
+        let white = if pos.side_to_move == sides::WHITE { 1 } else { -1 } * evaluate(&pos);
+        // The engine rounds the tapered score down; the model does not
+        let model = Sample::new(&pos, 0.5, &units).score(&weights);
+        assert!((model - white as f64).abs() < 1.0, "{}: {} vs {}", fen, model, white);
+    }
+}
+
+#[test]
+fn parses_labelled_positions() {
+    let (_, result) = parse_line(&format!("{} c9 \"1-0\";", &FENS[0][..62])).unwrap();
+    assert_eq!(result, 1.0);
+    let (pos, result) = parse_line(&format!("{} [0.5]", FENS[1])).unwrap();
+    assert_eq!((pos.side_to_move, result), (sides::BLACK, 0.5));
+    let (_, result) = parse_line(&format!("{} 0-1", FENS[2])).unwrap();
+    assert_eq!(result, 0.0);
+    assert!(parse_line(FENS[3]).is_none());
+    assert!(parse_line("not a fen [1.0]").is_none());
+}
+
+#[test]
+fn tuning_reduces_the_error() {
+    // Labels that disagree with the current weights
+    let units = unit_params();
+    let samples: Vec<Sample> = FENS
+        .iter()
+        .zip([0.0, 1.0, 0.0, 0.5])
+        .map(|(fen, result)| Sample::new(&Position::from_fen(fen).unwrap(), result, &units))
+        .collect();
+    let mut tuner = Tuner::new(samples, 2.0);
+    tuner.fit_k();
+    let before = tuner.error();
+    for _ in 0..50 {
+        tuner.epoch();
+    }
+    assert!(tuner.error() < before);
+    // Rounded weights keep the layout of the defaults
+    assert_eq!(tuner.params().to_vec().len(), DEFAULT_PARAMS.to_vec().len());
+}
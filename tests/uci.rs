@@ -0,0 +1,247 @@
+// tests/uci.rs
+
+use std::io::{self, Write};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use chessr::board::Position;
+use chessr::uci::{parse_limits, Uci};
+
+/// Output shared between a session and the test reading it
+#[derive(Clone, Default)]
+struct Captured(Arc<Mutex<Vec<u8>>>);
+
+impl Write for Captured {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Captured {
+    /// Lines written since the last call
+    fn take(&self) -> Vec<String> {
+        let bytes = std::mem::take(&mut *self.0.lock().unwrap());
+        String::from_utf8(bytes).unwrap().lines().map(str::to_string).collect()
+    }
+
+    /// Wait for the search thread to send its move, returning all lines up to it
+    fn search_output(&self) -> Vec<String> {
+        let start = Instant::now();
+        let mut lines = Vec::new();
+        while start.elapsed() < Duration::from_secs(30) {
+            lines.extend(self.take());
+            if let Some(at) = lines.iter().position(|line| line.starts_with("bestmove")) {
+                lines.truncate(at + 1);
+                return lines;
+            }
+            thread::sleep(Duration::from_millis(5));
+        }
+        panic!("no bestmove in {:?}", lines);
+    }
+
+    /// Wait for the search thread to send its move
+    fn bestmove(&self) -> String {
+        self.search_output().pop().unwrap()
+    }
+}
+
+fn session() -> (Uci, Captured) {
+    let out = Captured::default();
+    (Uci::with_output(out.clone()), out)
+}
+
+/// Send a command and return the lines it answered with straight away
+fn send(uci: &mut Uci, out: &Captured, command: &str) -> Vec<String> {
+    assert!(uci.handle_command(command));
+    out.take()
+}
+
+/// Send a `go` command and return the search output, ending with its `bestmove` line
+fn go(uci: &mut Uci, out: &Captured, command: &str) -> Vec<String> {
+    assert!(uci.handle_command(command));
+    out.search_output()
+}
+
+#[test]
+fn handshake() {
+    let (mut uci, out) = session();
+    let lines = send(&mut uci, &out, "uci");
+    assert!(lines[0].starts_with("id name chessr"));
+    assert!(lines.iter().any(|line| line.starts_with("option name Hash type spin")));
+    assert_eq!(lines.last().unwrap(), "uciok");
+    assert_eq!(send(&mut uci, &out, "isready"), ["readyok"]);
+    assert!(send(&mut uci, &out, "").is_empty());
+    assert_eq!(send(&mut uci, &out, "xyzzy"), ["info string unknown command: xyzzy"]);
+    assert!(!uci.handle_command("quit"));
+}
+
+#[test]
+fn position_commands() {
+    let (mut uci, out) = session();
+    assert_eq!(send(&mut uci, &out, "d"), [Position::startpos().to_fen()]);
+
+    send(&mut uci, &out, "position startpos moves e2e4 c7c5 g1f3");
+    let fen = "rnbqkbnr/pp1ppppp/8/2p5/4P3/5N2/PPPP1PPP/RNBQKB1R b KQkq - 1 2";
+    assert_eq!(send(&mut uci, &out, "d"), [fen]);
+
+    let kiwipete = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1";
+    send(&mut uci, &out, &format!("position fen {} moves e1g1 a6e2", kiwipete));
+    let fen = "r3k2r/p1ppqpb1/1n2pnp1/3PN3/1p2P3/2N2Q1p/PPPBbPPP/R4RK1 w kq - 0 2";
+    assert_eq!(send(&mut uci, &out, "d"), [fen]);
+    send(&mut uci, &out, &format!("position fen {}", kiwipete));
+    assert_eq!(send(&mut uci, &out, "d"), [kiwipete]);
+
+    // A bad command leaves the position alone
+    let lines = send(&mut uci, &out, "position startpos moves e2e4 e2e4");
+    assert_eq!(lines, ["info string illegal move: e2e4"]);
+    let lines = send(&mut uci, &out, "position fen 8/8/8 w - - 0 1");
+    assert!(lines[0].starts_with("info string invalid piece placement"), "{:?}", lines);
+    assert_eq!(send(&mut uci, &out, "position"), ["info string expected 'startpos' or 'fen'"]);
+    assert_eq!(send(&mut uci, &out, "d"), [kiwipete]);
+
+    send(&mut uci, &out, "ucinewgame");
+    assert_eq!(send(&mut uci, &out, "d"), [Position::startpos().to_fen()]);
+}
+
+#[test]
+fn options() {
+    let (mut uci, out) = session();
+    for command in [
+        "setoption name Hash value 2",
+        "setoption name threads value 2",
+        "setoption name MoveOverhead value 100000",
+        "setoption name OwnBook value true",
+        "setoption name BookSelection value best",
+        "setoption name BookFile value <empty>",
+        "setoption name SyzygyPath value <empty>",
+    ] {
+        assert!(send(&mut uci, &out, command).is_empty(), "{}", command);
+    }
+    for (command, reply) in [
+        ("setoption name Hash value big", "info string invalid Hash value: big"),
+        ("setoption name Threads value -1", "info string invalid Threads value: -1"),
+        ("setoption name MoveOverhead", "info string invalid MoveOverhead value: "),
+        (
+            "setoption name BookSelection value first",
+            "info string invalid BookSelection value: first",
+        ),
+        ("setoption name Style value very sharp", "info string unknown option: Style = very sharp"),
+        (
+            "setoption name SyzygyPath value /nonexistent",
+            "info string found 0 tablebases with up to 0 pieces",
+        ),
+    ] {
+        assert_eq!(send(&mut uci, &out, command), [reply]);
+    }
+    let lines = send(&mut uci, &out, "setoption name BookFile value /nonexistent/book.bin");
+    assert!(lines[0].starts_with("info string /nonexistent/book.bin: "), "{:?}", lines);
+}
+
+#[test]
+fn go_replies_with_a_legal_move() {
+    let (mut uci, out) = session();
+    let legal: Vec<String> =
+        Position::startpos().generate_legal_moves().iter().map(|mv| mv.to_string()).collect();
+    for command in ["go nodes 1", "go depth 1", "go movetime 20", "go wtime 50 btime 50"] {
+        let line = go(&mut uci, &out, command).pop().unwrap();
+        let mv = line.split_whitespace().nth(1).unwrap();
+        assert!(legal.iter().any(|legal| legal == mv), "{}: {}", command, line);
+    }
+
+    send(&mut uci, &out, "position fen 7k/8/6K1/8/8/8/8/1Q6 w - - 0 1");
+    assert_eq!(go(&mut uci, &out, "go depth 3").last().unwrap(), "bestmove b1b8");
+    send(&mut uci, &out, "position startpos");
+    let line = go(&mut uci, &out, "go depth 4 searchmoves h2h3 a2a3").pop().unwrap();
+    assert!(["bestmove h2h3", "bestmove a2a3"].iter().any(|mv| line.starts_with(mv)), "{}", line);
+}
+
+#[test]
+fn go_reports_each_iteration_before_the_move() {
+    let (mut uci, out) = session();
+    let lines = go(&mut uci, &out, "go depth 4");
+    let depths: Vec<&str> = lines
+        .iter()
+        .filter(|line| line.starts_with("info depth "))
+        .map(|line| line.split(' ').nth(2).unwrap())
+        .collect();
+    assert_eq!(depths, ["1", "2", "3", "4"], "{:?}", lines);
+    assert!(lines[..lines.len() - 1].iter().all(|line| line.starts_with("info ")), "{:?}", lines);
+    let last = lines[lines.len() - 2].split_whitespace().collect::<Vec<_>>();
+    let pv = last.iter().position(|&word| word == "pv").unwrap();
+    assert!(lines.last().unwrap().starts_with(&format!("bestmove {}", last[pv + 1])));
+}
+
+#[test]
+fn go_perft_divides() {
+    let (mut uci, out) = session();
+    let lines = send(&mut uci, &out, "go perft 1");
+    assert_eq!(lines.len(), 22, "{:?}", lines);
+    assert!(lines[..20].iter().all(|line| line.ends_with(": 1")));
+    assert!(lines.iter().any(|line| line == "g1f3: 1"));
+    assert_eq!(lines[20..], ["", "Nodes searched: 20"]);
+
+    send(&mut uci, &out, "position startpos moves e2e4");
+    let lines = send(&mut uci, &out, "go perft 2");
+    assert!(lines.iter().any(|line| line == "d7d5: 31"), "{:?}", lines);
+    assert_eq!(lines.last().unwrap(), "Nodes searched: 600");
+}
+
+#[test]
+fn stop_ends_an_infinite_search() {
+    let (mut uci, out) = session();
+    send(&mut uci, &out, "go infinite");
+    thread::sleep(Duration::from_millis(50));
+    assert!(out.take().iter().all(|line| !line.starts_with("bestmove")));
+    // The move is sent before `stop` returns
+    let lines = send(&mut uci, &out, "stop");
+    assert!(lines.last().unwrap().starts_with("bestmove "), "{:?}", lines);
+
+    send(&mut uci, &out, "go ponder wtime 1000 btime 1000");
+    thread::sleep(Duration::from_millis(50));
+    assert!(out.take().iter().all(|line| !line.starts_with("bestmove")));
+    assert!(!uci.handle_command("quit"));
+    uci.stop_search();
+    assert!(out.bestmove().starts_with("bestmove "));
+}
+
+#[test]
+fn ponderhit_starts_the_clock() {
+    let (mut uci, out) = session();
+    send(&mut uci, &out, "setoption name MoveOverhead value 0");
+    // Soft deadline 100ms, hard deadline 400ms: pondering runs past both
+    send(&mut uci, &out, "go ponder wtime 3000 btime 3000");
+    thread::sleep(Duration::from_millis(600));
+    assert!(out.take().iter().all(|line| !line.starts_with("bestmove")));
+    let start = Instant::now();
+    send(&mut uci, &out, "ponderhit");
+    assert!(out.bestmove().starts_with("bestmove "));
+    assert!(start.elapsed() >= Duration::from_millis(100), "{:?}", start.elapsed());
+}
+
+#[test]
+fn go_arguments() {
+    let pos = Position::startpos();
+    let args = "wtime 1000 btime -20 winc 10 binc 20 movestogo 12 movetime 300";
+    let limits = parse_limits(&pos, &args.split(' ').collect::<Vec<_>>());
+    assert_eq!((limits.wtime, limits.btime), (Some(1000), Some(0)));
+    assert_eq!((limits.winc, limits.binc), (Some(10), Some(20)));
+    assert_eq!((limits.movestogo, limits.movetime), (Some(12), Some(300)));
+    assert!(!limits.infinite && !limits.ponder);
+
+    let args = ["ponder", "searchmoves", "e2e4", "g1f3", "depth", "7", "infinite", "nodes", "x"];
+    let limits = parse_limits(&pos, &args);
+    assert!(limits.ponder && limits.infinite);
+    let moves: Vec<String> = limits.searchmoves.iter().map(|mv| mv.to_string()).collect();
+    assert_eq!(moves, ["e2e4", "g1f3"]);
+    assert_eq!((limits.depth, limits.nodes, limits.mate), (Some(7), None, None));
+
+    let limits = parse_limits(&pos, &["mate", "3", "searchmoves", "e2e5", "bogus"]);
+    assert_eq!(limits.mate, Some(3));
+    assert!(limits.searchmoves.is_empty());
+}